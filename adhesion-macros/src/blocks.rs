@@ -0,0 +1,196 @@
+//! Checks the blocks of a contracted `fn`, so that mistakes in them are
+//! reported at the block that's wrong instead of at the whole macro invocation.
+
+use proc_macro2::{Delimiter, Group, Ident, Spacing, TokenStream, TokenTree};
+use syn::parse::{ParseStream, Parser};
+use syn::{Pat, Signature, Token};
+
+const NAMES: &str =
+    "pre_err, pre, old, body, post, post_ok, post_err, post_some, post_none, double_check, on_panic, decreases";
+
+/// Checks that `blocks` is made of known blocks that are each used as often as
+/// they may be, and that the `fn` with `signature`, if there's one, can use
+/// them. Anything that's left to check is up to `contract_body!`, which relies
+/// on this to report malformed blocks.
+pub fn validate(blocks: TokenStream, signature: Option<&Signature>) -> syn::Result<()> {
+    let mut tokens = blocks.into_iter().peekable();
+    skip_inner_attributes(&mut tokens);
+
+    let mut seen: Vec<String> = Vec::new();
+    while let Some(token) = tokens.next() {
+        let name = match token {
+            TokenTree::Ident(name) => name,
+            token => {
+                return Err(syn::Error::new(
+                    token.span(),
+                    format!(
+                        "adhesion: unexpected `{}` in a contracted `fn`; expected a block like `pre {{ ... }}`",
+                        token
+                    ),
+                ));
+            }
+        };
+        let example = example(&name).ok_or_else(|| {
+            syn::Error::new(
+                name.span(),
+                format!(
+                    "adhesion: unknown block `{}`; expected one of {}",
+                    name, NAMES
+                ),
+            )
+        })?;
+
+        let arguments = match tokens.peek() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+                let group = group.clone();
+                tokens.next();
+                Some(group)
+            }
+            _ => None,
+        };
+        let block = match tokens.next() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group,
+            _ => {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!(
+                        "adhesion: `{}` must be followed by a block, like `{}`",
+                        name, example
+                    ),
+                ));
+            }
+        };
+        if let Some(ref arguments) = arguments {
+            validate_arguments(&name, arguments)?;
+        }
+        if let Some(signature) = signature {
+            validate_qualifiers(&name, signature)?;
+        }
+
+        // `on_panic` and the `post` blocks for a single variant may be used any
+        // number of times, as may `pre` blocks of callback contracts.
+        let repeatable = name.to_string().starts_with("post_")
+            || name == "on_panic"
+            || (name == "pre" && is_callback_contracts(&block));
+        if !repeatable {
+            if seen.iter().any(|seen| name == seen) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("adhesion: duplicate `{}` block", name),
+                ));
+            }
+            seen.push(name.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Skips `#![...]` attributes at the start of `tokens`.
+fn skip_inner_attributes(tokens: &mut std::iter::Peekable<impl Iterator<Item = TokenTree>>) {
+    while let Some(TokenTree::Punct(punct)) = tokens.peek() {
+        if punct.as_char() != '#' {
+            break;
+        }
+        tokens.next();
+        tokens.next();
+        tokens.next();
+    }
+}
+
+/// An example of how the block called `name` is written, if there's a block
+/// with that name.
+fn example(name: &Ident) -> Option<&'static str> {
+    Some(match name.to_string().as_str() {
+        "pre_err" => "pre_err { x >= 0 => Error::Negative }",
+        "pre" => "pre { ... }",
+        "old" => "old { ... }",
+        "body" => "body { ... }",
+        "post" => "post { ... }",
+        "post_ok" => "post_ok(value) { ... }",
+        "post_err" => "post_err(error) { ... }",
+        "post_some" => "post_some(value) { ... }",
+        "post_none" => "post_none { ... }",
+        "double_check" => "double_check { ... }",
+        "on_panic" => "on_panic { ... }",
+        "decreases" => "decreases { depth }",
+        _ => return None,
+    })
+}
+
+/// Checks what's in the parentheses after the name of a block, i.e., `(ret)`
+/// in `post(ret) { ... }`.
+fn validate_arguments(name: &Ident, arguments: &Group) -> syn::Result<()> {
+    match name.to_string().as_str() {
+        "double_check" => {
+            let mode = arguments.stream().to_string();
+            if mode != "unwind" {
+                return Err(syn::Error::new(
+                    arguments.span(),
+                    format!(
+                        "adhesion: `double_check` only takes `unwind`, like `double_check(unwind) {{ ... }}`; found `double_check({})`",
+                        mode
+                    ),
+                ));
+            }
+        }
+        "post" => {
+            // Anything that isn't a pattern followed by more is left for the
+            // compiler to complain about.
+            let two_patterns = (|input: ParseStream| {
+                Pat::parse_multi(input)?;
+                let two_patterns = input.peek(Token![,]);
+                input.parse::<TokenStream>()?;
+                Ok(two_patterns)
+            })
+            .parse2(arguments.stream())
+            .unwrap_or(false);
+            if two_patterns {
+                return Err(syn::Error::new(
+                    arguments.span(),
+                    format!(
+                        "adhesion: `post` takes a single pattern for the return value, like `post(ret)` or `post((a, b))`; found `post({})`",
+                        arguments.stream()
+                    ),
+                ));
+            }
+        }
+        "post_ok" | "post_err" | "post_some" => {}
+        _ => {
+            return Err(syn::Error::new(
+                arguments.span(),
+                format!("adhesion: `{}` doesn't take any arguments", name),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the block called `name` can be used in a `fn` with the
+/// qualifiers of `signature`.
+fn validate_qualifiers(name: &Ident, signature: &Signature) -> syn::Result<()> {
+    // The measures of `decreases` are kept per thread for as long as a call
+    // runs, which a `const fn` can't do, and which an `async fn` can't rely
+    // on, since it may be resumed on another thread.
+    if name == "decreases" {
+        let message = if signature.constness.is_some() {
+            "adhesion: a `const fn` can't keep track of its calls, so it can't use `decreases`"
+        } else if signature.asyncness.is_some() {
+            "adhesion: an `async fn` can't keep track of its calls across awaits, so it can't use `decreases`"
+        } else {
+            return Ok(());
+        };
+        return Err(syn::Error::new(name.span(), message));
+    }
+    Ok(())
+}
+
+/// Whether `block` is a `pre` block made of callback contracts, i.e.,
+/// `pre { f: fn_contract!(...) }`.
+fn is_callback_contracts(block: &Group) -> bool {
+    let tokens: Vec<_> = block.stream().into_iter().take(3).collect();
+    matches!(
+        tokens.as_slice(),
+        [TokenTree::Ident(_), TokenTree::Punct(colon), TokenTree::Ident(name)]
+            if colon.as_char() == ':' && colon.spacing() == Spacing::Alone && name == "fn_contract"
+    )
+}
@@ -0,0 +1,67 @@
+//! The implementation of `contract_body!(@check ...)`, which tells the two
+//! forms of a check block apart.
+
+use proc_macro2::{Group, Ident, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::{Expr, Token};
+
+/// `contract_body!` forwards a check block here with the path to the
+/// `adhesion` crate in front, i.e., `$crate; mode, Pre, fn_name, { ... }`.
+pub struct Check {
+    krate: TokenStream,
+    mode: TokenTree,
+    kind: Ident,
+    // A keyword like `for` in `contract_loop!`, which isn't an `Ident` to `syn`.
+    fn_name: TokenTree,
+    block: Group,
+}
+
+impl Parse for Check {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let krate = crate::parse_crate_path(input)?;
+        let mode = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let kind = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let fn_name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let block = input.parse()?;
+        Ok(Check {
+            krate,
+            mode,
+            kind,
+            fn_name,
+            block,
+        })
+    }
+}
+
+impl Check {
+    pub fn expand(self) -> TokenStream {
+        let Check {
+            krate,
+            mode,
+            kind,
+            fn_name,
+            block,
+        } = self;
+        if block.stream().is_empty() {
+            return quote!(());
+        }
+        // A block that parses as a condition with an optional message, like
+        // `{ x > 0, "message" }`, is in condition form. Anything else, such as
+        // statements or several expressions in a row, is run as-is.
+        let parser = Punctuated::<Expr, Token![,]>::parse_terminated;
+        let mut arguments = match parser.parse2(block.stream()) {
+            Ok(arguments) => arguments.into_iter(),
+            Err(_) => return block.into_token_stream(),
+        };
+        let condition = arguments.next();
+        let message: Vec<_> = arguments.collect();
+        quote! {
+            #krate::contract_body!(@condition #mode, #kind, #fn_name, #condition, [#(#message),*])
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! The implementation of `adhesion::contract!`.
+
+use proc_macro2::{Group, Literal, TokenStream};
+use quote::{quote, ToTokens};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, token, Attribute, FnArg, Ident, Signature, Token, Visibility};
+
+/// `contract!` forwards its input here with the path to the `adhesion` crate in
+/// front, i.e., `$crate; fn foo() { ... }`.
+///
+/// Every `fn` is expanded at once, so unlike a `macro_rules!` muncher, the
+/// recursion depth of an expansion doesn't grow with the number of `fn`s or
+/// with the length of their signatures.
+pub struct Contract {
+    krate: TokenStream,
+    items: Vec<Item>,
+    /// Whether the block is marked with `reentrancy_guard;`.
+    reentrancy_guard: bool,
+}
+
+// These only live for as long as an expansion, so their size doesn't matter.
+#[allow(clippy::large_enum_variant)]
+enum Item {
+    /// A top-level `double_check` block, which applies to every `fn` after it
+    /// until the next one.
+    DoubleCheck(GlobalDoubleCheck),
+    Fn {
+        attributes: Vec<Attribute>,
+        visibility: Visibility,
+        signature: Signature,
+        blocks: Group,
+    },
+}
+
+impl Parse for Contract {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let krate = crate::parse_crate_path(input)?;
+        let mut items = Vec::new();
+        let mut reentrancy_guard = false;
+        while !input.is_empty() {
+            if input.peek(Ident) && input.peek2(Token![;]) {
+                let marker: Ident = input.fork().parse()?;
+                if marker == "reentrancy_guard" {
+                    input.parse::<Ident>()?;
+                    input.parse::<Token![;]>()?;
+                    if reentrancy_guard {
+                        return Err(syn::Error::new(
+                            marker.span(),
+                            "adhesion: a `contract!` can only be marked with `reentrancy_guard` once",
+                        ));
+                    }
+                    reentrancy_guard = true;
+                    continue;
+                }
+            }
+            if let Some((_, block)) = crate::parse_named_block(input, "double_check")? {
+                items.push(Item::DoubleCheck(GlobalDoubleCheck {
+                    block,
+                    ..GlobalDoubleCheck::default()
+                }));
+                continue;
+            }
+            if let Some(double_check) = parse_double_check_with_modes(input)? {
+                items.push(Item::DoubleCheck(double_check));
+                continue;
+            }
+
+            let attributes = input.call(Attribute::parse_outer)?;
+            let visibility = input.parse()?;
+            let qualified = input.peek(Token![const])
+                || input.peek(Token![async])
+                || input.peek(Token![unsafe])
+                || input.peek(Token![extern]);
+            if !qualified && !input.peek(Token![fn]) {
+                return Err(input.error(
+                    "adhesion: expected a `fn` or a `double_check` block in `contract!`",
+                ));
+            }
+            let signature = input.parse().map_err(|error| {
+                if qualified {
+                    syn::Error::new(
+                        error.span(),
+                        "adhesion: expected `fn` after the qualifiers of a contracted `fn`",
+                    )
+                } else {
+                    error
+                }
+            })?;
+            let blocks: Group = input.parse()?;
+            crate::blocks::validate(blocks.stream(), Some(&signature))?;
+            items.push(Item::Fn {
+                attributes,
+                visibility,
+                signature,
+                blocks,
+            });
+        }
+        Ok(Contract {
+            krate,
+            items,
+            reentrancy_guard,
+        })
+    }
+}
+
+/// The number of `contract!` invocations expanded so far by this compiler
+/// process, which tells invocations apart even when they come from the same
+/// source location, like when a `macro_rules!` expands to several of them.
+static INVOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+impl Contract {
+    pub fn expand(self) -> TokenStream {
+        let Contract {
+            krate,
+            items,
+            reentrancy_guard,
+        } = self;
+        // Every `fn` of an invocation gets the same id, which stands in for a
+        // `static` that their bodies can't share. The module path keeps the
+        // ids of different crates apart.
+        let id = Literal::usize_unsuffixed(INVOCATIONS.fetch_add(1, Ordering::Relaxed));
+        let invocation = quote!(concat!(module_path!(), " #", #id));
+        let mut output = TokenStream::new();
+        let mut global_double_check = GlobalDoubleCheck::default();
+        for item in items {
+            match item {
+                Item::DoubleCheck(double_check) => global_double_check = double_check,
+                Item::Fn {
+                    attributes,
+                    visibility,
+                    signature,
+                    blocks,
+                } => {
+                    let fn_name = &signature.ident;
+                    let mode = crate::body_mode(&signature);
+                    let blocks = blocks.stream();
+                    let block = global_double_check.block(&krate, &signature);
+                    let modes = global_double_check.modes(&signature, &invocation, reentrancy_guard);
+                    output.extend(quote! {
+                        #(#attributes)*
+                        #visibility #signature {
+                            #krate::contract_body! {
+                                (fn_name #fn_name, global_double_check #block, mode #mode #modes)
+                                #blocks
+                            }
+                        }
+                    });
+                }
+            }
+        }
+        output
+    }
+}
+
+/// A top-level `double_check` block, along with the modes in its parentheses,
+/// like `double_check(outermost, unwind) { ... }`, or `double_check(invariant);`
+/// in place of a block.
+struct GlobalDoubleCheck {
+    block: TokenStream,
+    unwind: bool,
+    outermost: bool,
+    invariant: bool,
+}
+
+impl Default for GlobalDoubleCheck {
+    fn default() -> Self {
+        GlobalDoubleCheck {
+            block: quote!({}),
+            unwind: false,
+            outermost: false,
+            invariant: false,
+        }
+    }
+}
+
+impl GlobalDoubleCheck {
+    /// The block to check for the `fn` with `signature`. The invariant of
+    /// `Self` can only be checked by methods that borrow `self`, and not in a
+    /// `const fn`, so the rest don't check anything.
+    fn block(&self, krate: &TokenStream, signature: &Signature) -> TokenStream {
+        if !self.invariant {
+            return self.block.clone();
+        }
+        match signature.inputs.first() {
+            _ if signature.constness.is_some() => quote!({}),
+            Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() => {
+                let self_token = &receiver.self_token;
+                quote!({ #krate::__private::check_invariant(&*#self_token) })
+            }
+            _ => quote!({}),
+        }
+    }
+
+    /// The part of the context of `contract_body!` that describes the modes of
+    /// this block, and whether calls are guarded against reentrancy, for the
+    /// `fn` with `signature` in `invocation`, if any.
+    fn modes(
+        &self,
+        signature: &Signature,
+        invocation: &TokenStream,
+        reentrancy_guard: bool,
+    ) -> Option<TokenStream> {
+        // Calls are told apart by the invocation and the object that they're
+        // made on, so a `fn` that doesn't borrow one stands in with a `static`
+        // of its own, and is only ever nested in itself on a thread. A
+        // `const fn` can't keep track of calls at all, and an `async fn` may
+        // be finished on another thread than the one it started on, so both
+        // check every time.
+        let receiver = match signature.inputs.first() {
+            _ if signature.constness.is_some() || signature.asyncness.is_some() => None,
+            Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() => Some(receiver),
+            _ => None,
+        };
+        let outermost =
+            self.outermost && signature.constness.is_none() && signature.asyncness.is_none();
+        let reentrancy_guard = reentrancy_guard && receiver.is_some();
+        if !self.unwind && !outermost && !reentrancy_guard {
+            return None;
+        }
+        let unwind = self.unwind;
+        let object = match receiver {
+            _ if !outermost && !reentrancy_guard => quote!(),
+            Some(receiver) => {
+                let self_token = &receiver.self_token;
+                quote!(#invocation, #self_token as *const Self)
+            }
+            None => quote! {
+                #invocation,
+                {
+                    static __ADHESION_FN: u8 = 0;
+                    &__ADHESION_FN as *const u8
+                }
+            },
+        };
+        Some(quote! {
+            , global (unwind #unwind, outermost #outermost, reentrancy_guard #reentrancy_guard, object { #object })
+        })
+    }
+}
+
+/// Parses a block like `double_check(unwind) { ... }`, or an option like
+/// `double_check(invariant);`, if it's next in `input`.
+fn parse_double_check_with_modes(input: ParseStream) -> syn::Result<Option<GlobalDoubleCheck>> {
+    let fork = input.fork();
+    let matches = fork.parse::<Ident>().is_ok_and(|name| name == "double_check")
+        && fork.peek(token::Paren);
+    if !matches {
+        return Ok(None);
+    }
+    input.parse::<Ident>()?;
+    let modes;
+    parenthesized!(modes in input);
+    let mut double_check = GlobalDoubleCheck::default();
+    for mode in Punctuated::<Ident, Token![,]>::parse_terminated(&modes)? {
+        if mode == "unwind" {
+            double_check.unwind = true;
+        } else if mode == "outermost" {
+            double_check.outermost = true;
+        } else if mode == "invariant" {
+            double_check.invariant = true;
+        } else {
+            return Err(syn::Error::new(
+                mode.span(),
+                "adhesion: a top-level `double_check` only takes `unwind`, `outermost` and `invariant`, like `double_check(outermost) { ... }`",
+            ));
+        }
+    }
+    if double_check.invariant {
+        if input.peek(token::Brace) {
+            return Err(input.error(
+                "adhesion: `double_check(invariant)` checks `Self::check_invariant` instead of a block, so it's written as `double_check(invariant);`",
+            ));
+        }
+        input.parse::<Token![;]>()?;
+        return Ok(Some(double_check));
+    }
+    let block: Group = input.parse()?;
+    double_check.block = block.into_token_stream();
+    Ok(Some(double_check))
+}
@@ -0,0 +1,56 @@
+//! The implementation of `adhesion::contract_closure!`.
+
+use proc_macro2::{Group, Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{Expr, ExprClosure};
+
+/// `contract_closure!` forwards its input here with the path to the `adhesion`
+/// crate in front, i.e., `$crate; |x: u32| { ... }`.
+pub struct ContractClosure {
+    krate: TokenStream,
+    closure: ExprClosure,
+    blocks: Group,
+}
+
+impl Parse for ContractClosure {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let krate = crate::parse_crate_path(input)?;
+        let (closure, blocks): (ExprClosure, _) = crate::parse_item_with_body(input)?;
+        if closure.asyncness.is_some() || closure.constness.is_some() {
+            return Err(syn::Error::new(
+                closure.span(),
+                "adhesion: only ordinary closures can be contracted",
+            ));
+        }
+        crate::blocks::validate(blocks.stream(), None)?;
+        Ok(ContractClosure {
+            krate,
+            closure,
+            blocks,
+        })
+    }
+}
+
+impl ContractClosure {
+    pub fn expand(self) -> TokenStream {
+        let ContractClosure {
+            krate,
+            mut closure,
+            blocks,
+        } = self;
+        // Closures don't have a name of their own to report violations with.
+        let fn_name = Ident::new("closure", Span::call_site());
+        let blocks = blocks.stream();
+        closure.body = Box::new(Expr::Verbatim(quote! {
+            {
+                #krate::contract_body! {
+                    (fn_name #fn_name, global_double_check {}, mode normal)
+                    #blocks
+                }
+            }
+        }));
+        closure.into_token_stream()
+    }
+}
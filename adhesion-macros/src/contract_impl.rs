@@ -0,0 +1,167 @@
+//! The implementation of `adhesion::contract_impl!`.
+
+use proc_macro2::{Group, Ident, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{FnArg, ImplItem, ImplItemFn, ItemImpl, ReturnType, Type, Visibility};
+
+/// `contract_impl!` forwards its input here with the path to the `adhesion`
+/// crate in front, i.e., `$crate; impl Foo { ... }`.
+pub struct ContractImpl {
+    krate: TokenStream,
+    header: ItemImpl,
+    invariant: TokenStream,
+    items: Vec<ImplItem>,
+}
+
+impl Parse for ContractImpl {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let krate = crate::parse_crate_path(input)?;
+        let (header, body): (ItemImpl, _) = crate::parse_item_with_body(input)?;
+
+        let (invariant, items) = syn::parse::Parser::parse2(
+            |input: ParseStream| {
+                let mut invariant = None;
+                let mut items = Vec::new();
+                while !input.is_empty() {
+                    if let Some((keyword, block)) = crate::parse_named_block(input, "invariant")? {
+                        if invariant.replace(block).is_some() {
+                            return Err(syn::Error::new(
+                                keyword.span(),
+                                "adhesion: duplicate `invariant` block",
+                            ));
+                        }
+                    } else {
+                        items.push(input.parse()?);
+                    }
+                }
+                Ok((invariant, items))
+            },
+            body.stream(),
+        )?;
+        let invariant = invariant.ok_or_else(|| {
+            syn::Error::new(
+                body.span(),
+                "adhesion: `contract_impl!` needs an `invariant` block",
+            )
+        })?;
+
+        Ok(ContractImpl {
+            krate,
+            header,
+            invariant,
+            items,
+        })
+    }
+}
+
+impl ContractImpl {
+    pub fn expand(self) -> syn::Result<TokenStream> {
+        let ContractImpl {
+            krate,
+            mut header,
+            invariant,
+            items,
+        } = self;
+
+        for item in items {
+            let item = match item {
+                ImplItem::Fn(method) => {
+                    ImplItem::Fn(contract_method(&krate, &header, &invariant, method)?)
+                }
+                item => item,
+            };
+            header.items.push(item);
+        }
+
+        Ok(header.into_token_stream())
+    }
+}
+
+/// Adds the invariant to `method` if it's `pub` and takes `&self` or
+/// `&mut self`, or if it's a `pub` constructor that returns `Self`, unless
+/// it's a `const fn`.
+fn contract_method(
+    krate: &TokenStream,
+    header: &ItemImpl,
+    invariant: &TokenStream,
+    mut method: ImplItemFn,
+) -> syn::Result<ImplItemFn> {
+    let attributes = method.attrs.len();
+    method
+        .attrs
+        .retain(|attribute| !attribute.path().is_ident("no_invariant"));
+    let opted_out = method.attrs.len() != attributes;
+    // Methods of a trait impl are as public as the trait is.
+    let public = header.trait_.is_some() || !matches!(method.vis, Visibility::Inherited);
+    // A `const fn` can't run the invariant, which isn't const-evaluable.
+    if opted_out || !public || method.sig.constness.is_some() {
+        return Ok(method);
+    }
+
+    let fn_name = &method.sig.ident;
+    let mode = crate::body_mode(&method.sig);
+    let block = &method.block;
+    let body = match method.sig.inputs.first() {
+        Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() => {
+            quote! {
+                #krate::contract_body! {
+                    (fn_name #fn_name, global_double_check #invariant, mode #mode)
+                    body #block
+                }
+            }
+        }
+        Some(FnArg::Receiver(_)) => return Ok(method),
+        // The invariant is checked on the new value like the methods check it
+        // on `self`, so it's reported and enabled the same way, rather than
+        // like a `post` block.
+        _ if returns_self(&method.sig.output, &header.self_ty) => {
+            let constructed = Ident::new("__adhesion_constructed", Span::call_site());
+            let invariant = replace_self(invariant.clone(), &constructed);
+            quote! {
+                let #constructed = #krate::contract_body! {
+                    (fn_name #fn_name, global_double_check {}, mode #mode)
+                    body #block
+                };
+                if #krate::contract_body!(@enabled double_check) {
+                    #krate::contract_body!(@check #mode, GlobalDoubleCheck, #fn_name, #invariant)
+                }
+                #constructed
+            }
+        }
+        _ => return Ok(method),
+    };
+
+    method.block = syn::parse2(quote!({ #body }))?;
+    Ok(method)
+}
+
+fn returns_self(output: &ReturnType, self_ty: &Type) -> bool {
+    match *output {
+        ReturnType::Type(_, ref ty) => {
+            let ty = ty.to_token_stream().to_string();
+            ty == "Self" || ty == self_ty.to_token_stream().to_string()
+        }
+        ReturnType::Default => false,
+    }
+}
+
+/// Makes the invariant, which is written in terms of `self`, check `value`
+/// instead.
+fn replace_self(tokens: TokenStream, value: &Ident) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            // The span is left alone, so that `value` resolves to the same
+            // binding as the `post` that it's used in.
+            TokenTree::Ident(ref ident) if ident == "self" => TokenTree::Ident(value.clone()),
+            TokenTree::Group(group) => {
+                let mut replaced =
+                    Group::new(group.delimiter(), replace_self(group.stream(), value));
+                replaced.set_span(group.span());
+                TokenTree::Group(replaced)
+            }
+            token => token,
+        })
+        .collect()
+}
@@ -0,0 +1,202 @@
+//! The implementation of `adhesion::contract_trait!`.
+
+use proc_macro2::{Delimiter, Group, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, Attribute, FnArg, Ident, ItemTrait, Pat, Signature, TraitItem};
+
+/// `contract_trait!` forwards its input here with the path to the `adhesion`
+/// crate in front, i.e., `$crate; pub trait Foo { ... }`.
+pub struct ContractTrait {
+    krate: TokenStream,
+    header: ItemTrait,
+    global_double_check: TokenStream,
+    items: Vec<Item>,
+}
+
+// There are only ever a handful of these, so their size doesn't matter.
+#[allow(clippy::large_enum_variant)]
+enum Item {
+    Plain(TraitItem),
+    Contracted {
+        attributes: Vec<Attribute>,
+        signature: Signature,
+        blocks: TokenStream,
+    },
+}
+
+impl Parse for ContractTrait {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let krate = crate::parse_crate_path(input)?;
+        let (header, body): (ItemTrait, _) = crate::parse_item_with_body(input)?;
+
+        let (global_double_check, items) = syn::parse::Parser::parse2(parse_items, body.stream())?;
+        Ok(ContractTrait {
+            krate,
+            header,
+            global_double_check,
+            items,
+        })
+    }
+}
+
+fn parse_items(input: ParseStream) -> syn::Result<(TokenStream, Vec<Item>)> {
+    let mut global_double_check = None;
+    let mut items = Vec::new();
+    while !input.is_empty() {
+        if let Some((keyword, block)) = crate::parse_named_block(input, "double_check")? {
+            if global_double_check.replace(block).is_some() {
+                return Err(syn::Error::new(
+                    keyword.span(),
+                    "adhesion: duplicate `double_check` block",
+                ));
+            }
+            continue;
+        }
+
+        let fork = input.fork();
+        let is_fn = fork.call(Attribute::parse_outer).is_ok()
+            && fork.parse::<Signature>().is_ok()
+            && fork.peek(syn::token::Brace);
+        if is_fn {
+            let attributes = input.call(Attribute::parse_outer)?;
+            let signature = input.parse()?;
+            let content;
+            braced!(content in input);
+            items.push(Item::Contracted {
+                attributes,
+                signature,
+                blocks: content.parse()?,
+            });
+        } else {
+            items.push(Item::Plain(input.parse()?));
+        }
+    }
+    Ok((global_double_check.unwrap_or_else(|| quote!({})), items))
+}
+
+impl ContractTrait {
+    pub fn expand(self) -> syn::Result<TokenStream> {
+        let ContractTrait {
+            krate,
+            mut header,
+            global_double_check,
+            items,
+        } = self;
+
+        for item in items {
+            match item {
+                Item::Plain(item) => header.items.push(item),
+                Item::Contracted {
+                    attributes,
+                    signature,
+                    blocks,
+                } => {
+                    let (blocks, inner) = delegate_body(&signature, blocks)?;
+                    crate::blocks::validate(blocks.clone(), Some(&signature))?;
+                    let fn_name = &signature.ident;
+                    let mode = crate::body_mode(&signature);
+                    let mut wrapper_signature = signature.clone();
+                    if let Some((name, default)) = inner {
+                        // The wrapper only passes its arguments on, so
+                        // `mut` would be unused there.
+                        strip_mutability(&mut wrapper_signature);
+                        let mut inner_signature = signature.clone();
+                        inner_signature.ident = name;
+                        let inner = match default {
+                            Some(default) => quote!(#inner_signature #default),
+                            None => {
+                                // Patterns like `mut x` aren't allowed in a
+                                // method without a body either.
+                                strip_mutability(&mut inner_signature);
+                                quote!(#inner_signature;)
+                            }
+                        };
+                        header.items.push(syn::parse2(inner)?);
+                    }
+                    let wrapper = quote! {
+                        #(#attributes)*
+                        #wrapper_signature {
+                            #krate::contract_body! {
+                                (fn_name #fn_name, global_double_check #global_double_check, mode #mode)
+                                #blocks
+                            }
+                        }
+                    };
+                    header.items.push(syn::parse2(wrapper)?);
+                }
+            }
+        }
+
+        Ok(header.into_token_stream())
+    }
+}
+
+/// Replaces `body(inner)`, optionally followed by a default implementation, in
+/// the blocks of a contracted method with a `body` that calls `inner`. Returns
+/// the new blocks and what's needed to declare `inner` in the trait.
+#[allow(clippy::type_complexity)]
+fn delegate_body(
+    signature: &Signature,
+    blocks: TokenStream,
+) -> syn::Result<(TokenStream, Option<(Ident, Option<Group>)>)> {
+    let mut output = TokenStream::new();
+    let mut inner = None;
+    let mut tokens = blocks.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let (keyword, name) = match (&token, tokens.peek()) {
+            (TokenTree::Ident(keyword), Some(TokenTree::Group(group)))
+                if keyword == "body" && group.delimiter() == Delimiter::Parenthesis =>
+            {
+                (keyword.clone(), syn::parse2::<Ident>(group.stream())?)
+            }
+            _ => {
+                output.extend(Some(token));
+                continue;
+            }
+        };
+        tokens.next();
+        let default = match tokens.peek() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                Some(group.clone())
+            }
+            _ => None,
+        };
+        if default.is_some() {
+            tokens.next();
+        }
+
+        let arguments = signature
+            .inputs
+            .iter()
+            .map(|argument| match argument {
+                FnArg::Receiver(receiver) => Ok(receiver.self_token.to_token_stream()),
+                FnArg::Typed(typed) => match *typed.pat {
+                    Pat::Ident(ref pat) if pat.subpat.is_none() => Ok(pat.ident.to_token_stream()),
+                    ref pat => Err(syn::Error::new_spanned(
+                        pat,
+                        "adhesion: arguments of a method with `body(...)` must be plain names so they can be passed on",
+                    )),
+                },
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        let call = quote!(Self::#name(#(#arguments),*));
+        if signature.asyncness.is_some() {
+            output.extend(quote!(#keyword { #call.await }));
+        } else {
+            output.extend(quote!(#keyword { #call }));
+        }
+        inner = Some((name, default));
+    }
+    Ok((output, inner))
+}
+
+fn strip_mutability(signature: &mut Signature) {
+    for argument in &mut signature.inputs {
+        if let FnArg::Typed(typed) = argument {
+            if let Pat::Ident(ref mut pat) = *typed.pat {
+                pat.mutability = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! The implementation of `#[derive(adhesion::Invariant)]`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Expr, Fields, Index, Token};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate::crate_name();
+    let krate = quote!(::#krate);
+    let type_name = &input.ident;
+
+    let mut conditions = TokenStream::new();
+    for attribute in &input.attrs {
+        if !attribute.path().is_ident("invariant") {
+            continue;
+        }
+        let mut arguments = attribute
+            .parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?
+            .into_iter();
+        let condition = arguments.next().ok_or_else(|| {
+            syn::Error::new(
+                attribute.span(),
+                "adhesion: `#[invariant(...)]` needs a condition, like `#[invariant(self.count <= self.max)]`",
+            )
+        })?;
+        let message: Vec<_> = arguments.collect();
+        let message = if message.is_empty() {
+            quote!(::core::option::Option::None)
+        } else {
+            quote!(::core::option::Option::Some(format!(#(#message),*)))
+        };
+        // The line is the one of the attribute, rather than of the derive.
+        conditions.extend(quote_spanned! {condition.span()=>
+            if !#krate::__private::Condition::holds(#condition) {
+                return ::core::result::Result::Err(#krate::__private::violation(
+                    #krate::ViolationKind::Invariant,
+                    stringify!(#type_name),
+                    stringify!(#condition),
+                    file!(),
+                    line!(),
+                    #message,
+                    ::core::option::Option::None,
+                ));
+            }
+        });
+    }
+
+    let fields = match input.data {
+        Data::Struct(ref data) => {
+            let fields = data.fields.iter().enumerate().map(|(index, field)| {
+                let access = match field.ident {
+                    Some(ref name) => quote!(#name),
+                    None => Index::from(index).into_token_stream(),
+                };
+                check_field(&krate, quote!(&self.#access))
+            });
+            quote!(#(#fields)*)
+        }
+        Data::Enum(ref data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let name = &variant.ident;
+                let bindings: Vec<_> = (0..variant.fields.len())
+                    .map(|index| format_ident!("__adhesion_field_{}", index))
+                    .collect();
+                let pattern = match variant.fields {
+                    Fields::Named(ref fields) => {
+                        let names = fields.named.iter().map(|field| &field.ident);
+                        quote!({ #(#names: ref #bindings),* })
+                    }
+                    Fields::Unnamed(_) => quote!((#(ref #bindings),*)),
+                    Fields::Unit => quote!(),
+                };
+                let checks = bindings.iter().map(|binding| check_field(&krate, quote!(#binding)));
+                quote! {
+                    #type_name::#name #pattern => {
+                        #(#checks)*
+                    }
+                }
+            });
+            quote! {
+                match *self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(ref data) => {
+            return Err(syn::Error::new(
+                data.union_token.span(),
+                "adhesion: `Invariant` can only be derived for structs and enums",
+            ));
+        }
+    };
+
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::Invariant for #type_name #type_generics #where_clause {
+            fn check_invariant(&self) -> ::core::result::Result<(), #krate::ContractViolation> {
+                #[allow(unused_imports)]
+                use #krate::__private::{CheckField as _, SkipField as _};
+
+                #conditions
+                #fields
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+/// Checks the invariant of the field that `field` refers to, if its type has
+/// one.
+fn check_field(krate: &TokenStream, field: TokenStream) -> TokenStream {
+    quote! {
+        (&#krate::__private::Field(#field)).check_field()?;
+    }
+}
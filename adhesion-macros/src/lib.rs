@@ -0,0 +1,300 @@
+//! Attribute macros that put contracts on ordinary `fn` definitions. These are
+//! re-exported by the [`adhesion`](https://docs.rs/adhesion) crate, which is
+//! where they are documented and where they should be used from.
+#![deny(missing_docs)]
+
+extern crate proc_macro;
+
+mod blocks;
+mod check;
+mod contract;
+mod contract_closure;
+mod contract_impl;
+mod contract_trait;
+mod invariant;
+
+use proc_macro::TokenStream;
+use proc_macro_crate::FoundCrate;
+use proc_macro2::{Delimiter, Group, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, AttrStyle, Attribute, DeriveInput, Ident, ItemFn, Signature, Token};
+
+/// Adds a pre-condition check to a `fn`.
+#[proc_macro_attribute]
+pub fn pre(attribute: TokenStream, item: TokenStream) -> TokenStream {
+    expand(CheckKind::Pre, attribute, item)
+}
+
+/// Adds a post-condition check to a `fn`.
+#[proc_macro_attribute]
+pub fn post(attribute: TokenStream, item: TokenStream) -> TokenStream {
+    expand(CheckKind::Post, attribute, item)
+}
+
+/// Adds a check that runs both before and after the body of a `fn`.
+#[proc_macro_attribute]
+pub fn double_check(attribute: TokenStream, item: TokenStream) -> TokenStream {
+    expand(CheckKind::DoubleCheck, attribute, item)
+}
+
+/// Derives `Invariant` from `#[invariant(...)]` attributes and from the fields
+/// of a type.
+#[proc_macro_derive(Invariant, attributes(invariant))]
+pub fn derive_invariant(input: TokenStream) -> TokenStream {
+    match invariant::expand(parse_macro_input!(input as DeriveInput)) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __check(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as check::Check).expand().into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __contract(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as contract::Contract)
+        .expand()
+        .into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __contract_closure(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as contract_closure::ContractClosure)
+        .expand()
+        .into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __contract_trait(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as contract_trait::ContractTrait);
+    match input.expand() {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __contract_impl(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as contract_impl::ContractImpl);
+    match input.expand() {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Finds the name that the crate being compiled knows `adhesion` by. Unlike the
+/// `macro_rules!` front ends, attributes and derives aren't handed `$crate`, so
+/// this reads it out of the dependencies in the crate's `Cargo.toml` instead.
+fn crate_name() -> Ident {
+    let name = match proc_macro_crate::crate_name("adhesion") {
+        Ok(FoundCrate::Name(name)) => name,
+        // `adhesion`'s own tests, examples and doctests are separate crates
+        // that depend on it by its name.
+        Ok(FoundCrate::Itself) | Err(_) => "adhesion".to_owned(),
+    };
+    Ident::new(&name, Span::call_site())
+}
+
+/// Parses the path to the `adhesion` crate that its `macro_rules!` front ends
+/// put in front of their input, i.e., `$crate;`.
+fn parse_crate_path(input: ParseStream) -> syn::Result<TokenStream2> {
+    let mut krate = TokenStream2::new();
+    while !input.peek(Token![;]) {
+        krate.extend(Some(input.parse::<TokenTree>()?));
+    }
+    input.parse::<Token![;]>()?;
+    Ok(krate)
+}
+
+/// Parses an item that ends with a body in braces that isn't ordinary Rust.
+/// Everything up to the body is, so `syn` can parse it if the body is swapped
+/// out for an empty one. The body is returned separately.
+fn parse_item_with_body<T: Parse>(input: ParseStream) -> syn::Result<(T, Group)> {
+    let mut header = TokenStream2::new();
+    let body = loop {
+        match input.parse::<TokenTree>()? {
+            TokenTree::Group(ref group)
+                if group.delimiter() == Delimiter::Brace && input.is_empty() =>
+            {
+                break group.clone();
+            }
+            token => header.extend(Some(token)),
+        }
+    };
+    header.extend(Some(TokenTree::Group(Group::new(
+        Delimiter::Brace,
+        TokenStream2::new(),
+    ))));
+    Ok((syn::parse2(header)?, body))
+}
+
+/// The kind of body that `contract_body!` needs to generate for a `fn` with
+/// `signature`.
+fn body_mode(signature: &Signature) -> Ident {
+    let mode = if signature.constness.is_some() {
+        "const_fn"
+    } else if signature.asyncness.is_some() {
+        "async_fn"
+    } else {
+        "normal"
+    };
+    Ident::new(mode, Span::call_site())
+}
+
+/// Parses a block like `double_check { ... }` if it's next in `input`.
+fn parse_named_block(input: ParseStream, name: &str) -> syn::Result<Option<(Ident, TokenStream2)>> {
+    if input.peek(Ident) && input.peek2(syn::token::Brace) && input.fork().parse::<Ident>()? == name
+    {
+        let keyword = input.parse()?;
+        let block: Group = input.parse()?;
+        Ok(Some((keyword, block.into_token_stream())))
+    } else {
+        Ok(None)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CheckKind {
+    Pre,
+    Post,
+    DoubleCheck,
+}
+
+impl CheckKind {
+    fn from_attribute(attribute: &Attribute, krate: &Ident) -> Option<CheckKind> {
+        let path = attribute.path();
+        let segments: Vec<_> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        let name = match segments.as_slice() {
+            [name] => name,
+            [path, name] if krate == path => name,
+            _ => return None,
+        };
+        match name.as_str() {
+            "pre" => Some(CheckKind::Pre),
+            "post" => Some(CheckKind::Post),
+            "double_check" => Some(CheckKind::DoubleCheck),
+            _ => None,
+        }
+    }
+}
+
+/// The arguments of a single contract attribute, i.e., `x > 0, "message"` or,
+/// for `post`, `ret => ret > 0, "message"`.
+struct Check {
+    binding: Option<Ident>,
+    condition: TokenStream2,
+}
+
+impl Parse for Check {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let binding = if input.peek(Ident) && input.peek2(Token![=>]) {
+            let binding = input.parse()?;
+            input.parse::<Token![=>]>()?;
+            Some(binding)
+        } else {
+            None
+        };
+        Ok(Check {
+            binding,
+            condition: input.parse()?,
+        })
+    }
+}
+
+fn expand(kind: CheckKind, attribute: TokenStream, item: TokenStream) -> TokenStream {
+    let first_check = parse_macro_input!(attribute as Check);
+    let function = parse_macro_input!(item as ItemFn);
+    match expand_fn(kind, first_check, function) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_fn(kind: CheckKind, first_check: Check, function: ItemFn) -> syn::Result<TokenStream2> {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = function;
+
+    // The first contract attribute on a `fn` is the one that gets expanded, so
+    // it collects every other contract attribute below it to generate a single
+    // contract in the order they were written.
+    let krate = crate_name();
+    let mut checks = vec![(kind, first_check)];
+    let mut other_attributes = Vec::new();
+    for mut attribute in attrs {
+        match CheckKind::from_attribute(&attribute, &krate) {
+            Some(kind) => checks.push((kind, attribute.parse_args()?)),
+            None => {
+                // Inner attributes mean the same thing on the outside of a
+                // `fn`, which is where the contract body can't get in the way.
+                attribute.style = AttrStyle::Outer;
+                other_attributes.push(attribute);
+            }
+        }
+    }
+
+    let mut pre = TokenStream2::new();
+    let mut post = TokenStream2::new();
+    let mut double_check = TokenStream2::new();
+    let mut return_value: Option<Ident> = None;
+    let fn_name = &sig.ident;
+    let mode = body_mode(&sig);
+    for (kind, check) in checks {
+        let condition = check.condition;
+        let violation_kind = match kind {
+            CheckKind::Pre => quote!(Pre),
+            CheckKind::Post => quote!(Post),
+            CheckKind::DoubleCheck => quote!(DoubleCheck),
+        };
+        let assertion = quote! {
+            ::#krate::contract_body!(@check #mode, #violation_kind, #fn_name, { #condition });
+        };
+        match kind {
+            CheckKind::Pre => pre.extend(assertion),
+            CheckKind::DoubleCheck => double_check.extend(assertion),
+            CheckKind::Post => {
+                if let Some(binding) = check.binding {
+                    match return_value {
+                        Some(ref existing) if *existing != binding => {
+                            return Err(syn::Error::new(
+                                binding.span(),
+                                format!(
+                                    "adhesion: every `post` must bind the return value to the same name; expected `{}`",
+                                    existing
+                                ),
+                            ));
+                        }
+                        _ => return_value = Some(binding),
+                    }
+                }
+                post.extend(assertion);
+            }
+        }
+    }
+    let return_value = return_value.unwrap_or_else(|| Ident::new("_def", Span::call_site()));
+
+    let body = block.into_token_stream();
+    Ok(quote! {
+        #(#other_attributes)*
+        #vis #sig {
+            ::#krate::contract_body! {
+                (fn_name #fn_name, global_double_check {}, mode #mode)
+                pre { #pre }
+                body #body
+                post (#return_value) { #post }
+                double_check { #double_check }
+            }
+        }
+    })
+}
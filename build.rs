@@ -1,6 +1,75 @@
-extern crate skeptic;
+use std::env;
+use std::fs;
+use std::path::Path;
 
-fn main() {
-    skeptic::generate_doc_tests(&["README.md"]);
+/// Pulls the body of the first `rust,skt-main` code block out of a Markdown
+/// template, in the same format that `skeptic` used to consume, and splits it
+/// into the code that goes before and after the `{}` placeholder.
+fn read_template(markdown: &str) -> (String, String) {
+    let mut lines = markdown
+        .lines()
+        .skip_while(|line| !(line.starts_with("```") && line.contains("skt-main")));
+    lines.next().expect("no skt-main template found");
+    let body: Vec<&str> = lines.take_while(|line| !line.starts_with("```")).collect();
+    let body = body.join("\n");
+
+    let placeholder = body.find("{}").expect("no placeholder found in template");
+    let unescape = |s: &str| s.replace("{{", "{").replace("}}", "}");
+    (unescape(&body[..placeholder]), unescape(&body[placeholder + 2..]))
+}
+
+/// Rewrites every `skt-main` code block in `markdown` into a full program by
+/// applying `template`, so that `rustdoc` can run them as doctests.
+fn expand_code_blocks(markdown: &str, template: &(String, String)) -> String {
+    let mut output = String::new();
+    let mut current_block: Option<Vec<&str>> = None;
+
+    for line in markdown.lines() {
+        match current_block.take() {
+            Some(mut block) => {
+                if line.starts_with("```") {
+                    output.push_str(&template.0);
+                    output.push_str(&block.join("\n"));
+                    output.push_str(&template.1);
+                    output.push('\n');
+                    output.push_str(line);
+                    output.push('\n');
+                } else {
+                    block.push(line);
+                    current_block = Some(block);
+                }
+            }
+            None => {
+                if line.starts_with("```") && line.contains("skt-main") {
+                    let flags: Vec<&str> = line
+                        .trim_start_matches('`')
+                        .split(',')
+                        .filter(|flag| *flag != "skt-main")
+                        .collect();
+                    output.push_str("```");
+                    output.push_str(&flags.join(","));
+                    output.push('\n');
+                    current_block = Some(Vec::new());
+                } else {
+                    output.push_str(line);
+                    output.push('\n');
+                }
+            }
+        }
+    }
+
+    output
 }
 
+fn main() {
+    println!("cargo:rerun-if-changed=README.md");
+    println!("cargo:rerun-if-changed=README.md.skt.md");
+
+    let readme = fs::read_to_string("README.md").expect("failed to read README.md");
+    let template = fs::read_to_string("README.md.skt.md").expect("failed to read README.md.skt.md");
+    let doctests = expand_code_blocks(&readme, &read_template(&template));
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR not set");
+    fs::write(Path::new(&out_dir).join("README-doctests.md"), doctests)
+        .expect("failed to write README doctests");
+}
@@ -31,17 +31,16 @@ impl FromStr for Date {
                     (let year, "-", let month, "-", let day) => Date { year, month, day }
                 }
             }
-            post(ret) {
-                if let &Ok(ref date) = &ret {
-                    assert!(date.year >= 1900);
-                    assert!(date.month >= 1 && date.month <= 12);
-                    assert!(date.day >= 1 && date.day <= 31);
-                }
+            post_ok(date) {
+                assert!(date.year >= 1900);
+                assert!(date.month >= 1 && date.month <= 12);
+                assert!(date.day >= 1 && date.day <= 31);
             }
         }
     }
 }
 
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for Date {
     contract! {
         fn to_string(&self) -> String {
@@ -53,7 +52,7 @@ impl ToString for Date {
 
                 let parts = s.split("-");
                 let parts_lengths: Vec<usize> = parts.clone().map(|part| part.len()).collect();
-                assert!(&parts_lengths[..] == &[4, 2, 2]);
+                assert!(parts_lengths[..] == [4, 2, 2]);
                 assert!(parts.clone().all(|part| i32::from_str(part).is_ok()))
             }
         }
@@ -7,13 +7,14 @@
 #[macro_use]
 extern crate adhesion;
 
+#[allow(clippy::wrong_self_convention)]
 trait WithinPrecisionOf {
     fn is_within_precision_of(self, target: Self, tolerance: Self) -> bool;
 }
 
 impl WithinPrecisionOf for f64 {
     fn is_within_precision_of(self, target: Self, tolerance: Self) -> bool {
-        return (self - target).abs() < tolerance.abs();
+        (self - target).abs() < tolerance.abs()
     }
 }
 
@@ -33,7 +34,9 @@ contract! {
             // square root of a negative number requires more imagination than
             // we're willing to implement here, so let's disallow floats as
             // part of our contract.
-            assert!(!(x < 0_f64), "cannot compute root of negative number x");
+            #[allow(clippy::neg_cmp_op_on_partial_ord)]
+            let non_negative = !(x < 0_f64);
+            assert!(non_negative, "cannot compute root of negative number x");
         }
         body {
             if x == 0_f64 {
@@ -46,17 +49,24 @@ contract! {
             let mut previous = initial_guess;
 
             let mut i = 1;
-            loop {
-                current = previous - (previous * previous - x) / (2_f64 * previous);
-                println!("  Iteration {}: {}", i, current);
-
-                if current.is_nan() || current.is_within_precision_of(previous, precision) {
-                    break current;
+            // A guess that isn't positive would never get us to a root, so we
+            // check that on every iteration.
+            contract_loop! {
+                invariant {
+                    previous > 0_f64, "guess {} is not positive", previous
                 }
+                loop {
+                    current = previous - (previous * previous - x) / (2_f64 * previous);
+                    println!("  Iteration {}: {}", i, current);
+
+                    if current.is_nan() || current.is_within_precision_of(previous, precision) {
+                        break current;
+                    }
 
-                previous = current;
+                    previous = current;
 
-                i += 1;
+                    i += 1;
+                }
             }
         }
     }
@@ -0,0 +1,80 @@
+use crate::violation::ContractViolation;
+
+/// A type with an invariant, i.e., a condition that every value of it is
+/// expected to hold between calls to its methods.
+///
+/// This is usually implemented with `#[derive(Invariant)]`, which builds
+/// [`check_invariant`](#tymethod.check_invariant) out of the
+/// `#[invariant(...)]` attributes on the type. Each one takes a condition in
+/// terms of `self` and an optional message, like `pre` blocks written in
+/// condition form do, and they're checked in the order they were written.
+/// Afterwards, every field whose type implements `Invariant` is checked too,
+/// along with the fields of the variant that an enum holds. Fields whose type
+/// is a type parameter are only checked if it's bound by `Invariant` on the
+/// type itself.
+///
+/// The top-level `double_check(invariant);` option of
+/// [`contract!`](./macro.contract.html) checks it around every method.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate adhesion;
+/// #
+/// # fn main () {
+/// use adhesion::{Invariant, ViolationKind};
+///
+/// #[derive(Invariant)]
+/// #[invariant(self.count <= self.max, "{} is past {}", self.count, self.max)]
+/// struct Counter {
+///     count: u32,
+///     max: u32,
+/// }
+///
+/// #[derive(Invariant)]
+/// enum Slot {
+///     Empty,
+///     Counting(Counter),
+/// }
+///
+/// assert!(Counter { count: 1, max: 2 }.check_invariant().is_ok());
+///
+/// let broken = Slot::Counting(Counter { count: 3, max: 2 });
+/// let violation = broken.check_invariant().unwrap_err();
+/// assert!(violation.kind == ViolationKind::Invariant);
+/// assert!(violation.fn_name == "Counter");
+/// assert!(violation.message.as_deref() == Some("3 is past 2"));
+/// assert!(Slot::Empty.check_invariant().is_ok());
+/// # }
+/// ```
+pub trait Invariant {
+    /// Checks the invariant, returning a description of the first part of it
+    /// that doesn't hold, if any.
+    fn check_invariant(&self) -> Result<(), ContractViolation>;
+}
+
+impl<T: Invariant + ?Sized> Invariant for Box<T> {
+    fn check_invariant(&self) -> Result<(), ContractViolation> {
+        (**self).check_invariant()
+    }
+}
+
+impl<T: Invariant> Invariant for Option<T> {
+    fn check_invariant(&self) -> Result<(), ContractViolation> {
+        match *self {
+            Some(ref value) => value.check_invariant(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: Invariant> Invariant for [T] {
+    fn check_invariant(&self) -> Result<(), ContractViolation> {
+        self.iter().try_for_each(Invariant::check_invariant)
+    }
+}
+
+impl<T: Invariant> Invariant for Vec<T> {
+    fn check_invariant(&self) -> Result<(), ContractViolation> {
+        self[..].check_invariant()
+    }
+}
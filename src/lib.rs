@@ -3,45 +3,547 @@
 //! checking out:
 //!
 //! * The README.md in source, most easily viewed at Github
-//!     [here](https://github.com/ErichDonGubler/adhesion-rs)
+//!   [here](https://github.com/ErichDonGubler/adhesion-rs)
 //! * This crate's [example files](https://github.com/ErichDonGubler/adhesion-rs/tree/master/examples)
 //! * This crate's [test suite](https://github.com/ErichDonGubler/adhesion-rs/tree/master/tests)
 #![deny(missing_docs)]
 #![doc(html_root_url = "https://docs.rs/adhesion/0.4.0")]
 
-mod parse_generics_shim_util;
+extern crate adhesion_macros;
+
+mod invariant;
+mod violation;
+
+pub use invariant::Invariant;
+pub use violation::{
+    default_violation_handler, set_violation_handler, Blame, ContractViolation, ViolationKind,
+};
+
+/// An attribute alternative to a `pre` block, which keeps the contracted `fn`
+/// an ordinary item that `rustfmt` and IDEs understand. Its arguments are the
+/// same as those of `assert!`, and a failure panics with a
+/// [`ContractViolation`](./struct.ContractViolation.html).
+///
+/// `pre`, [`post`](./attr.post.html) and [`double_check`](./attr.double_check.html)
+/// attributes can be stacked on the same `fn` in any combination, and are
+/// checked in the same order documented for [`contract!`](./macro.contract.html).
+/// Several attributes of the same kind are checked in the order they were
+/// written.
+///
+/// ```
+/// # extern crate adhesion;
+/// # #[macro_use]
+/// # extern crate galvanic_assert;
+/// #
+/// # fn main () {
+/// use adhesion::{double_check, post, pre};
+///
+/// #[pre(divisor != 0, "cannot divide by zero")]
+/// #[post(quotient => quotient <= dividend)]
+/// #[double_check(dividend < 1000)]
+/// fn divide(dividend: u32, divisor: u32) -> u32 {
+///     dividend / divisor
+/// }
+///
+/// assert!(divide(10, 2) == 5);
+/// assert_that!(divide(10, 0), panics); // pre failure
+/// assert_that!(divide(1000, 1), panics); // double_check failure
+/// # }
+/// ```
+#[doc(inline)]
+pub use adhesion_macros::pre;
+
+/// An attribute alternative to a `post` block. The return value can be bound
+/// with `name =>` before the condition, as in `#[post(ret => ret > 0)]`; every
+/// `post` attribute on the same `fn` must use the same name for it. See
+/// [`pre`](./attr.pre.html) for more details.
+#[doc(inline)]
+pub use adhesion_macros::post;
+
+/// An attribute alternative to a `double_check` block. See
+/// [`pre`](./attr.pre.html) for more details.
+#[doc(inline)]
+pub use adhesion_macros::double_check;
+
+/// Derives [`Invariant`](./trait.Invariant.html) from `#[invariant(...)]`
+/// attributes on a struct or an enum, and from its fields. See the trait for
+/// more details.
+#[doc(inline)]
+pub use adhesion_macros::Invariant;
+
+#[doc(hidden)]
+pub mod __private {
+    pub const PRE_ENABLED: bool = !cfg!(feature = "disable-pre");
+    pub const POST_ENABLED: bool = !cfg!(feature = "disable-post");
+    pub const DOUBLE_CHECK_ENABLED: bool = !cfg!(feature = "disable-double-check");
+    pub const DEBUG_ONLY: bool = cfg!(feature = "debug-only");
+
+    pub use adhesion_macros::{
+        __check, __contract, __contract_closure, __contract_impl, __contract_trait,
+    };
+    pub use crate::violation::{report, violation};
+    use crate::{ContractViolation, Invariant};
+
+    use std::any::Any;
+    use std::cell::RefCell;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    /// The type of a check block written as a single expression: `bool` for a
+    /// condition, or `()` for something like `assert!(...)` that checks
+    /// itself.
+    pub trait Condition {
+        fn holds(self) -> bool;
+    }
+
+    impl Condition for bool {
+        fn holds(self) -> bool {
+            self
+        }
+    }
+
+    impl Condition for () {
+        fn holds(self) -> bool {
+            true
+        }
+    }
+
+    pub use std::panic::resume_unwind;
+
+    thread_local! {
+        static CALLS: RefCell<Vec<CallKey>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// What a call is made into: the `contract!` invocation that the method
+    /// belongs to, and the type and address of the object. An object and the
+    /// field at its start share an address, so the type tells them apart.
+    #[derive(Clone, Copy, PartialEq)]
+    struct CallKey {
+        invocation: &'static str,
+        object_type: &'static str,
+        object: usize,
+    }
+
+    /// A call into a contracted method that needs to know whether it's the
+    /// outermost call into the same object through the same `contract!`
+    /// invocation on this thread.
+    pub struct Call {
+        key: CallKey,
+        outermost: bool,
+    }
+
+    impl Call {
+        pub fn enter<T: ?Sized>(invocation: &'static str, object: *const T) -> Self {
+            let key = CallKey {
+                invocation,
+                object_type: std::any::type_name::<T>(),
+                object: object as *const () as usize,
+            };
+            let outermost = CALLS.with(|calls| {
+                let mut calls = calls.borrow_mut();
+                let outermost = !calls.contains(&key);
+                calls.push(key);
+                outermost
+            });
+            Call { key, outermost }
+        }
+
+        pub fn is_outermost(&self) -> bool {
+            self.outermost
+        }
+    }
+
+    impl Drop for Call {
+        fn drop(&mut self) {
+            // Calls on a thread end in the reverse order of how they started,
+            // so the latest matching entry is the one for this call. `async
+            // fn`s, which could end elsewhere, don't keep track of calls.
+            let _ = CALLS.try_with(|calls| {
+                let mut calls = calls.borrow_mut();
+                if let Some(index) = calls.iter().rposition(|key| *key == self.key) {
+                    calls.remove(index);
+                }
+            });
+        }
+    }
+
+    /// Calls the closure that `body` is evaluated in, catching a panic so that
+    /// the checks for it can run.
+    pub fn catch_unwind<R>(body: impl FnOnce() -> R) -> std::thread::Result<R> {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(body))
+    }
+
+    /// Catches a panic while polling the `async` block that `body` is
+    /// evaluated in, like [`catch_unwind`] does for the closure.
+    pub struct CatchUnwind<F>(Pin<Box<F>>);
+
+    impl<F: Future> CatchUnwind<F> {
+        pub fn new(body: F) -> Self {
+            CatchUnwind(Box::pin(body))
+        }
+    }
+
+    impl<F: Future> Future for CatchUnwind<F> {
+        type Output = std::thread::Result<F::Output>;
+
+        fn poll(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+            let body = self.0.as_mut();
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| body.poll(context))) {
+                Ok(Poll::Pending) => Poll::Pending,
+                Ok(Poll::Ready(value)) => Poll::Ready(Ok(value)),
+                Err(payload) => Poll::Ready(Err(payload)),
+            }
+        }
+    }
+
+    macro_rules! callbacks {
+        ($($name: ident($($argument: ident: $Argument: ident),*);)*) => {
+            $(
+                /// Wraps `callback` in a closure that calls it through
+                /// `checked`. Taking both as arguments lets the types of the
+                /// arguments of `checked` be inferred from `callback`, so that
+                /// its checks can use them before it calls `callback`.
+                pub fn $name<F, R, $($Argument),*>(
+                    callback: F,
+                    checked: impl Fn(&F, $($Argument),*) -> R,
+                ) -> impl Fn($($Argument),*) -> R
+                where
+                    F: Fn($($Argument),*) -> R,
+                {
+                    move |$($argument),*| checked(&callback, $($argument),*)
+                }
+            )*
+        };
+    }
+
+    callbacks! {
+        callback_0();
+        callback_1(a: A);
+        callback_2(a: A, b: B);
+        callback_3(a: A, b: B, c: C);
+        callback_4(a: A, b: B, c: C, d: D);
+    }
+
+    /// Keeps the value of the `decreases` block of a `contract_loop!` from the
+    /// previous iteration.
+    #[derive(Default)]
+    pub struct Variant<T>(Option<T>);
+
+    impl<T: PartialOrd + Default> Variant<T> {
+        /// Moves on to the `value` of the next iteration, returning what's
+        /// wrong with it, if anything. Values that can't be compared, like
+        /// `NaN`, count as wrong.
+        pub fn next(&mut self, value: T) -> Option<&'static str> {
+            let problem = variant_problem(
+                &value,
+                self.0.as_ref(),
+                "didn't decrease since the previous iteration",
+            );
+            self.0 = Some(value);
+            problem
+        }
+    }
+
+    /// What's wrong with the next `value` of a measure that has to stay
+    /// non-negative and strictly decrease since `previous`, if anything, with
+    /// `not_decreasing` as the problem for the latter.
+    fn variant_problem<T: PartialOrd + Default>(
+        value: &T,
+        previous: Option<&T>,
+        not_decreasing: &'static str,
+    ) -> Option<&'static str> {
+        let non_negative = *value >= T::default();
+        let decreased = previous.is_none_or(|previous| *value < *previous);
+        if !non_negative {
+            Some("went below zero")
+        } else if !decreased {
+            Some(not_decreasing)
+        } else {
+            None
+        }
+    }
+
+    thread_local! {
+        static MEASURES: RefCell<Vec<(usize, Box<dyn Any>)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// A call into a contracted `fn` with a `decreases` block, whose measure
+    /// stays on the stack of measures of this thread for as long as it runs.
+    /// Each `fn` is told apart by the address of a `static` of its own.
+    pub struct Measure {
+        key: usize,
+    }
+
+    impl Measure {
+        /// Checks the measure of a call against the one of the latest call
+        /// into the same `fn` that's still running on this thread, returning
+        /// what's wrong with it, if anything.
+        pub fn check<T: PartialOrd + Default + 'static>(
+            key: &'static u8,
+            value: &T,
+        ) -> Option<&'static str> {
+            let key = key as *const u8 as usize;
+            MEASURES.with(|measures| {
+                let measures = measures.borrow();
+                // A generic `fn` shares its `static` between the types that
+                // it's used with, which may not all have the same measure.
+                let previous = measures
+                    .iter()
+                    .rev()
+                    .filter(|&&(other, _)| other == key)
+                    .find_map(|(_, previous)| previous.downcast_ref::<T>());
+                variant_problem(
+                    value,
+                    previous,
+                    "didn't decrease since the call it recursed from",
+                )
+            })
+        }
+
+        pub fn enter<T: 'static>(key: &'static u8, value: T) -> Self {
+            let key = key as *const u8 as usize;
+            MEASURES.with(|measures| measures.borrow_mut().push((key, Box::new(value))));
+            Measure { key }
+        }
+    }
+
+    impl Drop for Measure {
+        fn drop(&mut self) {
+            // Like with `Call`, only the latest matching entry is removed.
+            let _ = MEASURES.try_with(|measures| {
+                let mut measures = measures.borrow_mut();
+                if let Some(index) = measures.iter().rposition(|&(key, _)| key == self.key) {
+                    measures.remove(index);
+                }
+            });
+        }
+    }
+
+    /// Reports the first part of the invariant of `value` that doesn't hold,
+    /// if any, for `double_check(invariant)`.
+    pub fn check_invariant<T: Invariant + ?Sized>(value: &T) {
+        if let Err(violation) = value.check_invariant() {
+            report(violation);
+        }
+    }
+
+    /// A field of a type with a derived `Invariant`. Its invariant is checked
+    /// through [`CheckField`] if its type implements `Invariant`, and method
+    /// resolution falls back to [`SkipField`] otherwise, which takes one more
+    /// reference to get to.
+    pub struct Field<'a, T: ?Sized>(pub &'a T);
+
+    pub trait CheckField {
+        fn check_field(&self) -> Result<(), ContractViolation>;
+    }
+
+    impl<T: Invariant + ?Sized> CheckField for Field<'_, T> {
+        fn check_field(&self) -> Result<(), ContractViolation> {
+            self.0.check_invariant()
+        }
+    }
+
+    pub trait SkipField {
+        fn check_field(&self) -> Result<(), ContractViolation>;
+    }
+
+    impl<T: ?Sized> SkipField for &Field<'_, T> {
+        fn check_field(&self) -> Result<(), ContractViolation> {
+            Ok(())
+        }
+    }
+
+    /// Calls the closure that `body` is evaluated in. Requiring `FnOnce` here
+    /// lets `body` return a borrow of something it captured, like a `&mut T`
+    /// taken from an argument, which an inferred `FnMut` closure can't.
+    #[inline(always)]
+    pub fn evaluate<R>(body: impl FnOnce() -> R) -> R {
+        body()
+    }
+}
+
+#[cfg(doctest)]
+#[doc = include_str!(concat!(env!("OUT_DIR"), "/README-doctests.md"))]
+struct ReadmeDoctests;
 
 /// Converts one or more `fn` definitions inside to be contracted functions that
 /// may have pre- and post-condition checks. The following blocks are valid inside
 /// of a `fn` definition:
 ///
 /// 1. `pre` -- runs once before `body`.
-/// 2. `body` -- the main part of the function. This is the reason the function
-///     exists!
-/// 3. `post` -- runs once after `body`.
+/// 2. `old` -- runs once right before `body`. Any `let` bindings made here
+///    stay in scope for `post`, which makes it the place to take snapshots of
+///    values that `body` may change.
+/// 3. `body` -- the main part of the function. This is the reason the function
+///    exists!
+/// 4. `post` -- runs once after `body`.
 /// 5. `double_check` -- runs twice; after `pre`, and before `post`.
+/// 6. `post_ok`, `post_err`, `post_some` and `post_none` -- run after `post`,
+///    but only when the return value is the matching variant of a `Result`
+///    or an `Option`.
+/// 7. `pre_err` -- runs first, and makes a `fn` that returns a `Result`
+///    return an `Err` early instead of panicking when one of its conditions
+///    fails.
+/// 8. `on_panic` -- runs only when `body` panics, before the panic carries on.
+/// 9. `decreases` -- runs once after `pre`, and checks the measure of a
+///    recursive `fn`.
 ///
 /// A `double_check` block may be used at the top level of a `contract!`
 /// invocation, which will be used by ALL `fn` definitions inside. This block
 /// is particularly useful for structs, where invariants for all data members
 /// may need to be maintained across method calls.
 ///
+/// Written as `double_check(outermost) { ... }`, the top-level block is only
+/// checked on the outermost call into an object, so methods may break it in
+/// between calls to other contracted methods on the same object, as long as
+/// it holds again by the time the outermost call returns. Calls are told
+/// apart by the `contract!` invocation that they go through and by the type
+/// and address of the `&self` or `&mut self` that they're made on, and are
+/// tracked per thread, so a call into a field of the object is an outermost
+/// call into the field. A `fn` without such a receiver only counts as nested
+/// in calls to itself. A `const fn` checks it on every call, and so does an
+/// `async fn`, which may be finished on another thread than the one it started
+/// on. Both modes can be combined, as in `double_check(outermost, unwind)`.
+///
+/// Instead of a block, `double_check(invariant);` checks the
+/// [`Invariant`](./trait.Invariant.html) of `Self` in the same places, for
+/// every method with a `&self` or `&mut self` receiver. A failure is reported
+/// with the violation that `Self::check_invariant` returns. It can be combined
+/// with the other modes, as in `double_check(invariant, outermost);`.
+///
+/// Marking a `contract!` invocation with `reentrancy_guard;` at the top level
+/// makes each method in it with a `&self` or `&mut self` receiver check that
+/// no other contracted method of the invocation is running on the same object
+/// on this thread, like when a callback calls back into it. A call that breaks
+/// this is reported as a violation of kind [`ViolationKind::Reentrancy`]
+/// before any other checks run. Methods of other types, like those of a field,
+/// and methods of other `contract!` invocations don't count. Neither do
+/// `const` and `async` methods, which aren't guarded, since an `async fn` may
+/// be finished on another thread than the one it started on.
+///
+/// Note that the guard can't tell a callback from any other call, so it
+/// doesn't only catch re-entries through callbacks: a contracted method that
+/// calls another contracted method of the same invocation on `self` directly
+/// is reported too. Helpers that are called like that have to be moved out of
+/// the guarded invocation, e.g., into a plain `impl` block.
+///
 /// When every contract block is being utilized, the final order of the checks
 /// inserted into the contract definition are as follows:
 ///
-/// 1. `pre`
-/// 2. `double_check` of the `contract!` block
-/// 3. `double_check` of the `fn` definition
-/// 4. `body`
-/// 5. `double_check` of the `contract!` block
-/// 6. `double_check` of the `fn` definition
-/// 7. `post`
+/// 1. `pre_err`
+/// 2. `pre`
+/// 3. `decreases`
+/// 4. `double_check` of the `contract!` block
+/// 5. `double_check` of the `fn` definition
+/// 6. `old`
+/// 7. `body`
+/// 8. `double_check` of the `contract!` block
+/// 9. `double_check` of the `fn` definition
+/// 10. `post`
+/// 11. `post_ok`, `post_err`, `post_some` and `post_none`
 ///
 /// No blocks in this macro are required, nor is any specific order required.
 ///
-/// It should be noted that conditional compilation is NOT handled by this
-/// library, and that if conditional compilation is desired, [`cfg` statements](https://doc.rust-lang.org/beta/reference/attributes.html#conditional-compilation)
-/// should be used like with any most other Rust code.
+/// `body` is evaluated inside of a closure, so an early `return` or a `?` in
+/// `body` still runs the trailing `double_check` blocks and `post`. The value
+/// being returned is what gets bound to the parameter of `post`.
+///
+/// The parameter of `post` may be any pattern instead of a single name. A
+/// pattern is matched against the return value like in a `match`, so
+/// `post((quotient, remainder))` destructures a tuple, and a refutable pattern
+/// like `post(Ok(value))` only checks the return values that it matches. Parts
+/// that aren't `Copy` have to be bound with `ref` so that they can still be
+/// returned afterwards, and `post(ref ret)` borrows the whole return value
+/// instead of moving it.
+///
+/// `post_ok(value)`, `post_err(error)` and `post_some(value)` bind what's
+/// inside of the matching variant, by reference, to the pattern in their
+/// parameter, which may be left out along with its parentheses. There may be
+/// any number of them, including several of the same kind, and they run in
+/// the order they were written.
+///
+/// A `pre_err` block is a list of conditions, each optionally followed by the
+/// error to return when it fails, like
+/// `pre_err { x >= 0 => MyError::Negative, x < 100 }`. The error is converted
+/// with `From`, like `?` would, and a condition without one returns a
+/// [`ContractViolation`](./struct.ContractViolation.html) converted the same
+/// way. Since callers rely on these errors, `pre_err` isn't affected by the
+/// cargo features below, and its failures don't go through the violation
+/// handler.
+///
+/// A `decreases` block is a single expression, like `decreases { depth }`,
+/// which makes sure that a recursive `fn` ends. Its value has to be
+/// non-negative, i.e., at least its `Default`, and strictly less than the one
+/// of the call into the same `fn` that it was made from, if any, on the same
+/// thread. This catches runaway recursion with a violation of kind
+/// [`ViolationKind::Decreases`] instead of a stack overflow. Any type that's
+/// `PartialOrd`, `Default` and `'static` can be used, like the numbers. The
+/// checks only run if they're enabled by the `debug-only` feature. Neither a
+/// `const fn` nor an `async fn` can use this block, since the measures are kept
+/// per thread and an `async fn` may be suspended and resumed on another one.
+///
+/// A `pre` block may also declare contracts for callback arguments instead of
+/// conditions, like `pre { f: fn_contract!(|x| pre { x > 0 }) }`, which are
+/// checked on every call of the callback. See
+/// [`fn_contract!`](./macro.fn_contract.html) for more details.
+///
+/// If `body` panics, none of the checks after it run, unless they're written
+/// as `double_check(unwind) { ... }`, at the top level or in a `fn`, or as an
+/// `on_panic` block. These run after the panic is caught, in the order they
+/// were written after a top-level one, and the panic carries on afterwards if
+/// none of them fail. A failure replaces the panic with a violation, so that
+/// a `body` which leaves things in a broken state when it panics gets caught
+/// even if the panic is recovered from with `std::panic::catch_unwind`. A
+/// `const fn` can't catch panics, so these can't be used in one.
+///
+/// A contracted `fn` may be `const`, `async`, `unsafe` or `extern`, with or
+/// without an ABI. The body of an `async fn` is evaluated inside of an `async`
+/// block instead of a closure, so its checks only run once the future is
+/// polled, and `post` runs after `body` has resolved. A `const fn` can't call a
+/// closure, so an early `return` in its `body` skips the checks after it. Its
+/// checks have to be const-evaluable, so conditions have to be `bool`s and
+/// messages are limited to what `panic!` accepts in a `const` context. Failed
+/// conditions there panic directly instead of going through the violation
+/// handler. A violation inside of an `extern "C" fn` aborts the process
+/// instead of unwinding, unless the violation handler returns.
+///
+/// A `pre`, `post` or `double_check` block can be written as a single
+/// condition with an optional message instead of as statements, like
+/// `pre { x > 0, "{} is not positive", x }`. When a condition fails, a
+/// [`ContractViolation`](./struct.ContractViolation.html) describing it is
+/// passed to the handler set with
+/// [`set_violation_handler`](./fn.set_violation_handler.html), which by
+/// default prints it to stderr and panics with it as the payload. Blocks made
+/// of statements are run as-is, so they panic however their own code does,
+/// i.e., with the message of an `assert!`. A block is in condition form when
+/// all of it parses as one expression followed by optional message arguments,
+/// so a lone expression like `assert!(...)` or `if ... { panic!() }` is a
+/// condition that evaluates to `()`, which always holds.
+///
+/// The [`pre`](./attr.pre.html), [`post`](./attr.post.html) and
+/// [`double_check`](./attr.double_check.html) attributes provide the same
+/// checks for an ordinary `fn` without wrapping it in a macro invocation.
+///
+/// Which checks get run can be controlled for a whole build with the following
+/// cargo features of this crate:
+///
+/// * `disable-pre` -- skips every `pre` block.
+/// * `disable-post` -- skips every `post` block.
+/// * `disable-double-check` -- skips every `double_check` block, including
+///   top-level ones.
+/// * `debug-only` -- skips every check unless `debug_assertions` are enabled
+///   for the crate that contains the contract, which is normally the case for
+///   debug builds only.
+///
+/// Disabled checks are still type-checked, but are never run. An `old` block
+/// is always run, since `post` may still refer to its bindings, so whatever it
+/// costs to take a snapshot, like cloning a collection, is still paid with
+/// `disable-post`, or with `debug-only` in a release build. An expensive
+/// snapshot can be skipped along with `post` by checking
+/// `cfg!(feature = ...)` or `cfg!(debug_assertions)` in `old` itself. Finer-grained
+/// conditional compilation is left to [`cfg` attributes](https://doc.rust-lang.org/reference/conditional-compilation.html),
+/// like with most other Rust code.
 ///
 /// # Examples
 ///
@@ -98,10 +600,16 @@ mod parse_generics_shim_util;
 ///         }
 ///
 ///         fn tick_up(&mut self) {
+///             old {
+///                 let previous = self.count;
+///             }
 ///             body {
 ///                 // Force a panic if this overflows, even in release
 ///                 self.count = self.count.checked_add(1).unwrap();
 ///             }
+///             post {
+///                 assert!(self.count == previous + 1);
+///             }
 ///         }
 ///
 ///         fn tick_down(&mut self) {
@@ -116,267 +624,1004 @@ mod parse_generics_shim_util;
 /// ```
 #[macro_export]
 macro_rules! contract {
-    (
-        @muncher,
-        [double_check $double_check: tt],
-        $(#[$attribute: meta])*
-        $(pub$(($access_modifier: ident))*)* fn $fn_name: ident $($tail: tt)*
-    ) => {
-        contract_fn! {
-            [callback contract(@muncher, [double_check $double_check],), double_check $double_check],
-            $(#[$attribute])*
-            $(pub$(($access_modifier))*)* fn $fn_name $($tail)*
+    ($($tokens: tt)*) => {
+        $crate::__private::__contract! { $crate; $($tokens)* }
+    };
+}
+
+/// Puts contracts on the methods of a `trait`, so that every implementation of
+/// it is held to them. This is written like a `trait` item, except that
+/// contracted methods have the same blocks as a `fn` in
+/// [`contract!`](./macro.contract.html) in place of a default body.
+///
+/// Instead of a `body` block, a contracted method can name another method with
+/// `body(inner_method)`. The contracted method then becomes a provided method
+/// that calls `inner_method` between its checks, and `inner_method` is
+/// declared with the same signature for implementors to write. Callers go
+/// through the contracted method, so that the checks apply no matter which
+/// implementation gets called. `body(inner_method)` may be followed by a block
+/// that becomes the default implementation of `inner_method`. The arguments of
+/// such a method have to be plain names, since they get passed on as-is.
+///
+/// A contracted method with a `body` block instead is an ordinary provided
+/// method with checks, which implementors may override along with its
+/// contract. Methods ending with `;` and other items are left as they are.
+///
+/// A `double_check` block at the top of the trait applies to every contracted
+/// method, like one at the top level of `contract!`.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate adhesion;
+/// # #[macro_use]
+/// # extern crate galvanic_assert;
+/// #
+/// # fn main () {
+/// contract_trait! {
+///     pub trait Stack {
+///         double_check {
+///             self.len() <= 3
+///         }
+///
+///         fn len(&self) -> usize;
+///
+///         fn push(&mut self, value: u32) {
+///             pre {
+///                 self.len() < 3, "the stack is full"
+///             }
+///             old {
+///                 let len = self.len();
+///             }
+///             body(push_unchecked)
+///             post {
+///                 self.len() == len + 1
+///             }
+///         }
+///     }
+/// }
+///
+/// struct VecStack(Vec<u32>);
+///
+/// impl Stack for VecStack {
+///     fn len(&self) -> usize {
+///         self.0.len()
+///     }
+///
+///     fn push_unchecked(&mut self, value: u32) {
+///         self.0.push(value);
+///     }
+/// }
+///
+/// let mut stack = VecStack(Vec::new());
+/// stack.push(1);
+/// stack.push(2);
+/// stack.push(3);
+/// assert_that!(VecStack(vec![1, 2, 3]).push(4), panics);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! contract_trait {
+    ($($tokens: tt)*) => {
+        $crate::__private::__contract_trait! { $crate; $($tokens)* }
+    };
+}
+
+/// Applies an invariant to a whole `impl` block. This is written like an
+/// `impl` item with an `invariant` block among its items, which has the same
+/// form as a `double_check` block in [`contract!`](./macro.contract.html).
+///
+/// The methods inside are ordinary Rust, and the invariant is added to them as
+/// follows:
+///
+/// * `pub` methods that take `&self` or `&mut self` check it like a top-level
+///   `double_check` block, i.e., before and after their body.
+/// * `pub` constructors, which take no `self` and return `Self`, check it on
+///   the value they return after their body, where `self` in the invariant
+///   stands for that value.
+/// * Other methods, i.e., private helpers, methods that take `self` by value
+///   and `const fn`s, are left alone. A `const fn` can't call the trait
+///   methods that an invariant usually needs, so it's skipped the same way as
+///   by `double_check(invariant);` in [`contract!`](./macro.contract.html).
+///
+/// `async fn`s are checked the same way, around the future they return. Either
+/// way, a failure is reported as a violation of kind
+/// [`ViolationKind::GlobalDoubleCheck`], and the checks are turned off along
+/// with `double_check` blocks.
+///
+/// Every method of a trait `impl` counts as `pub`. A method can opt out with a
+/// `#[no_invariant]` attribute, and methods can still have contract attributes
+/// like [`pre`](./attr.pre.html) of their own, which are checked around the
+/// invariant.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate adhesion;
+/// # #[macro_use]
+/// # extern crate galvanic_assert;
+/// #
+/// # fn main () {
+/// struct Counter {
+///     count: u32,
+///     max: u32,
+/// }
+///
+/// contract_impl! {
+///     impl Counter {
+///         invariant {
+///             self.count <= self.max, "count is past its max"
+///         }
+///
+///         pub fn new(max: u32) -> Self {
+///             Counter { count: 0, max }
+///         }
+///
+///         pub fn tick_up(&mut self) {
+///             self.count += 1;
+///         }
+///
+///         #[no_invariant]
+///         pub fn reset(&mut self, max: u32) {
+///             self.max = max;
+///             self.clamp();
+///         }
+///
+///         fn clamp(&mut self) {
+///             self.count = self.count.min(self.max);
+///         }
+///     }
+/// }
+///
+/// let mut counter = Counter::new(1);
+/// counter.tick_up();
+/// counter.reset(0);
+/// assert!(counter.count == 0);
+/// assert_that!({ Counter::new(0).tick_up(); }, panics);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! contract_impl {
+    ($($tokens: tt)*) => {
+        $crate::__private::__contract_impl! { $crate; $($tokens)* }
+    };
+}
+
+/// Makes a closure with a contract, which is written like a closure whose body
+/// is made of the same blocks as a `fn` in [`contract!`](./macro.contract.html).
+/// The blocks run in the same order, and the closure captures whatever they
+/// use, so captured variables can be checked like arguments. A `move` closure
+/// moves them all into itself, like usual.
+///
+/// The blocks are checked when the closure is called, not when it's made, and
+/// violations are reported with `closure` as the name of the `fn`. `async` and
+/// `const` closures can't be contracted.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate adhesion;
+/// # #[macro_use]
+/// # extern crate galvanic_assert;
+/// #
+/// # fn main () {
+/// let max = 10;
+/// let double = contract_closure!(|x: u32| -> u32 {
+///     pre {
+///         x <= max, "{} is too big", x
+///     }
+///     body {
+///         x * 2
+///     }
+///     post(doubled) {
+///         doubled <= max * 2
+///     }
+/// });
+///
+/// assert!(double(5) == 10);
+/// assert_that!(double(11), panics);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! contract_closure {
+    ($($tokens: tt)*) => {
+        $crate::__private::__contract_closure! { $crate; $($tokens)* }
+    };
+}
+
+/// Declares a contract for a callback argument of a contracted `fn`. This is
+/// only valid inside of a `pre` block of [`contract!`](./macro.contract.html),
+/// written as `name: fn_contract!(...)` for each callback to check, like so:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate adhesion;
+/// #
+/// # fn main () {
+/// use adhesion::{Blame, ContractViolation};
+/// use std::panic::catch_unwind;
+///
+/// contract! {
+///     fn apply_to_small(x: u32, f: impl Fn(u32) -> u32) -> u32 {
+///         pre {
+///             f: fn_contract!(|x| pre { x > 0 } post(r) { r < 100 })
+///         }
+///         body {
+///             f(x)
+///         }
+///     }
+/// }
+///
+/// assert!(apply_to_small(5, |x| x * 2) == 10);
+///
+/// // The contracted `fn` called `f` with a bad argument.
+/// let violation = catch_unwind(|| apply_to_small(0, |x| x)).unwrap_err();
+/// let violation = violation.downcast_ref::<ContractViolation>().unwrap();
+/// assert!(violation.kind.blame() == Blame::Callee);
+///
+/// // The caller passed in an `f` that returned a bad value.
+/// let violation = catch_unwind(|| apply_to_small(5, |x| x * 100)).unwrap_err();
+/// let violation = violation.downcast_ref::<ContractViolation>().unwrap();
+/// assert!(violation.kind.blame() == Blame::Caller);
+/// # }
+/// ```
+///
+/// The callback is shadowed by a closure that checks the `pre` block on its
+/// arguments before every call and the `post` block on its return value
+/// afterwards, which may each be left out, and bind the return value to a
+/// name like `post` in `contract!` does. Following the higher-order contracts
+/// of Findler and Felleisen, a failure of the `pre` block blames the
+/// contracted `fn`, which passed the bad arguments, and a failure of the
+/// `post` block blames the caller, which passed in the bad callback. These
+/// are reported as [`ViolationKind::CallbackPre`](./enum.ViolationKind.html)
+/// and [`ViolationKind::CallbackPost`](./enum.ViolationKind.html), whose
+/// [`blame`](./enum.ViolationKind.html#method.blame) says which is which.
+///
+/// The callback has to implement `Fn`, and may take up to 4 arguments, each
+/// bound to a name. It's shadowed after the `old` block runs, so `pre` and
+/// `old` still see the original. Its checks may borrow from the contracted
+/// `fn`, like its other arguments, in which case the shadowed callback can't
+/// outlive the call.
+#[macro_export]
+macro_rules! fn_contract {
+    ($($tokens: tt)*) => {
+        compile_error!("adhesion: `fn_contract!` can only be used in a `pre` block of a contracted `fn`, like `pre { f: fn_contract!(...) }`")
+    };
+}
+
+/// Checks a loop invariant and a loop variant on every iteration of a `loop`,
+/// `while` or `for` loop, which is written after them:
+///
+/// * `invariant` -- has the same form as a `double_check` block in
+///   [`contract!`](./macro.contract.html), and is checked at the start of
+///   every iteration, i.e., after the condition of a `while` loop and after
+///   the next item of a `for` loop has been bound.
+/// * `decreases` -- a single expression, whose value has to be non-negative,
+///   i.e., at least its `Default`, and strictly less than on the previous
+///   iteration at the start of every iteration. This makes sure that the loop
+///   ends. Any type that's `PartialOrd` and `Default` can be used, like the
+///   numbers or tuples of them, which are compared lexicographically.
+///
+/// Both blocks are optional, and may be written in any order. The loop itself
+/// is ordinary Rust, with an optional label, so `break` and `continue` work as
+/// usual, and the value of a `loop` that it breaks with is the value of the
+/// whole `contract_loop!`.
+///
+/// A failure is reported like one in `contract!`, as a
+/// [`ViolationKind::LoopInvariant`](./enum.ViolationKind.html) or a
+/// [`ViolationKind::LoopVariant`](./enum.ViolationKind.html), with the number
+/// of the iteration it happened on, counting from 1, in its `iteration`. The
+/// checks only run if they're enabled by the `debug-only` feature, like any
+/// others.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate adhesion;
+/// # #[macro_use]
+/// # extern crate galvanic_assert;
+/// #
+/// # fn main () {
+/// fn gcd(mut a: u32, mut b: u32) -> u32 {
+///     contract_loop! {
+///         invariant {
+///             a > 0 || b > 0
+///         }
+///         decreases {
+///             b
+///         }
+///         while b != 0 {
+///             (a, b) = (b, a % b);
+///         }
+///     }
+///     a
+/// }
+///
+/// assert!(gcd(12, 18) == 6);
+///
+/// let mut steps = 0;
+/// let last = contract_loop! {
+///     decreases {
+///         10 - steps
+///     }
+///     loop {
+///         if steps == 10 {
+///             break steps;
+///         }
+///         steps += 1;
+///     }
+/// };
+/// assert!(last == 10);
+///
+/// assert_that!(
+///     {
+///         let mut x = 0;
+///         contract_loop! {
+///             decreases {
+///                 x
+///             }
+///             while x < 3 {
+///                 x += 1;
+///             }
+///         }
+///     },
+///     panics
+/// );
+/// # }
+/// ```
+#[macro_export]
+macro_rules! contract_loop {
+    (@blocks (invariant {}, decreases $decreases: tt) invariant {$($invariant: tt)*} $($tail: tt)*) => {
+        $crate::contract_loop!(@blocks (invariant {$($invariant)*}, decreases $decreases) $($tail)*)
+    };
+    (@blocks (invariant $invariant: tt, decreases []) decreases {$($decreases: tt)*} $($tail: tt)*) => {
+        $crate::contract_loop!(@blocks (invariant $invariant, decreases [{$($decreases)*}]) $($tail)*)
+    };
+    (@blocks $state: tt invariant {$($block: tt)*} $($tail: tt)*) => {
+        compile_error!("adhesion: duplicate `invariant` block")
+    };
+    (@blocks $state: tt decreases {$($block: tt)*} $($tail: tt)*) => {
+        compile_error!("adhesion: duplicate `decreases` block")
+    };
+    (@blocks $state: tt) => {
+        compile_error!("adhesion: expected a `loop`, `while` or `for` loop after the blocks of `contract_loop!`")
+    };
+    (@blocks $state: tt $($tail: tt)+) => {
+        $crate::contract_loop!(@header $state [] $($tail)+)
+    };
+    // Everything up to the body of the loop is its header, whose first tokens
+    // tell what kind of loop it is.
+    (@header $state: tt [$($header: tt)*] {$($body: tt)*}) => {
+        $crate::contract_loop!(@loop $state [$($header)*] {$($body)*})
+    };
+    (@header $state: tt [$($header: tt)*] $next: tt $($tail: tt)+) => {
+        $crate::contract_loop!(@header $state [$($header)* $next] $($tail)+)
+    };
+    (@header $state: tt [$($header: tt)*] $last: tt) => {
+        compile_error!("adhesion: expected the body of a loop at the end of `contract_loop!`")
+    };
+    (@loop $state: tt [$($label: lifetime:)? loop] $body: tt) => {
+        $crate::contract_loop!(@expand $state, loop, [$($label:)? loop], $body)
+    };
+    (@loop $state: tt [$($label: lifetime:)? while $($condition: tt)+] $body: tt) => {
+        $crate::contract_loop!(@expand $state, while, [$($label:)? while $($condition)+], $body)
+    };
+    (@loop $state: tt [$($label: lifetime:)? for $($iteration: tt)+] $body: tt) => {
+        $crate::contract_loop!(@expand $state, for, [$($label:)? for $($iteration)+], $body)
+    };
+    (@loop $state: tt [$($header: tt)*] $body: tt) => {
+        compile_error!("adhesion: expected a `loop`, `while` or `for` loop after the blocks of `contract_loop!`")
+    };
+    (@expand (invariant $invariant: tt, decreases [$($decreases: tt)*]), $keyword: ident, [$($header: tt)*], {$($body: tt)*}) => {
+        {
+            let mut __adhesion_iteration: usize = 0;
+            $crate::contract_loop!(@variant_init __adhesion_variant $($decreases)*);
+            $($header)* {
+                __adhesion_iteration += 1;
+                if $crate::contract_body!(@enabled build) {
+                    $crate::contract_body!(@check (iteration __adhesion_iteration), LoopInvariant, $keyword, $invariant);
+                    $crate::contract_loop!(@variant $keyword, __adhesion_iteration, __adhesion_variant $($decreases)*);
+                }
+                $($body)*
+            }
         }
     };
+    (@variant_init $variant: ident) => {};
+    (@variant_init $variant: ident {$measure: expr}) => {
+        let mut $variant = $crate::__private::Variant::default();
+    };
+    (@variant $keyword: ident, $iteration: ident, $variant: ident) => {};
+    (@variant $keyword: ident, $iteration: ident, $variant: ident {$measure: expr}) => {
+        if let Some(problem) = $variant.next($measure) {
+            $crate::__private::report($crate::__private::violation(
+                $crate::ViolationKind::LoopVariant,
+                stringify!($keyword),
+                stringify!($measure),
+                file!(),
+                line!(),
+                Some(String::from(problem)),
+                Some($iteration),
+            ));
+        }
+    };
+    (@variant $keyword: ident, $iteration: ident, $variant: ident {$($measure: tt)*}) => {
+        compile_error!("adhesion: a `decreases` block has to be a single expression")
+    };
+    ($($tokens: tt)*) => {
+        $crate::contract_loop!(@blocks (invariant {}, decreases []) $($tokens)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! contract_body {
     (
-        @muncher,
-        [double_check $_old_double_check: tt],
-        double_check $double_check: tt
-        $($tail: tt)+
+        ($($context: tt)*)
+        #![$inner_attribute: meta]
+        $($tail: tt)*
     ) => {
-        contract! {
-            @muncher,
-            [double_check $double_check],
-            $($tail)+
+        $crate::contract_body! {
+            @processing_blocks
+            (pre_err {}, pre {}, old {}, callbacks [], decreases {}, body {}, post (binding _def) {}, post_variants [], double_check {}, unwind [], context ($($context)*), #![$inner_attribute])
+            $($tail)*
         }
     };
     (
-        @muncher,
-        [double_check $_old_double_check: tt],
-    ) => {};
-    (
+        ($($context: tt)*)
         $($tail: tt)*
     ) => {
-        contract! {
-            @muncher,
-            [double_check {}],
+        $crate::contract_body! {
+            @processing_blocks
+            (pre_err {}, pre {}, old {}, callbacks [], decreases {}, body {}, post (binding _def) {}, post_variants [], double_check {}, unwind [], context ($($context)*))
             $($tail)*
         }
     };
-}
-
-#[doc(hidden)]
-#[macro_export]
-macro_rules! contract_fn {
     (
-        [callback $($callback: ident ($($callback_args: tt)*))*, double_check $double_check: tt],
-        $(#[$attribute: meta])*
-        $(pub$(($access_modifier: ident))*)* fn $fn_name: ident $($tail: tt)*
+        @processing_blocks
+        (pre_err {}, pre $pre: tt, old $old: tt, callbacks $callbacks: tt, decreases $decreases: tt, body $body: tt, post $return_value: tt $post: tt, post_variants $post_variants: tt, double_check $double_check: tt, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        pre_err {$($pre_err: tt)*}
+        $($tail: tt)*
     ) => {
-        parse_generics_shim! {
-            { constr },
-            then contract_fn!(@after_bracket_generics, [callback $($callback($($callback_args)*))*, double_check $double_check], $(#[$attribute])* $(pub$(($access_modifier))*)* fn $fn_name,),
+        $crate::contract_body! {
+            @processing_blocks
+            (pre_err {$($pre_err)*}, pre $pre, old $old, callbacks $callbacks, decreases $decreases, body $body, post $return_value $post, post_variants $post_variants, double_check $double_check, unwind $unwind, context $context $(, #![$inner_attribute])*)
             $($tail)*
         }
     };
+    // A `pre` block made of `fn_contract!`s declares contracts for callback
+    // arguments instead of conditions. Such blocks may be mixed with an
+    // ordinary one.
     (
-        @after_bracket_generics,
-        [callback $($callback: ident ($($callback_args: tt)*))*, double_check $double_check: tt],
-        $(#[$attribute: meta])*
-        $(pub$(($access_modifier: ident))*)* fn $fn_name: ident,
-        {
-            constr: [$($constr: tt)*],
-        },
-        $args: tt $( -> $return_type: ty)* where $($tail: tt)*
+        @processing_blocks
+        (pre_err $pre_err: tt, pre $pre: tt, old $old: tt, callbacks [$($callback: tt)*], decreases $decreases: tt, body $body: tt, post $return_value: tt $post: tt, post_variants $post_variants: tt, double_check $double_check: tt, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        pre {$($name: ident: fn_contract!($($contract: tt)*)),+ $(,)?}
+        $($tail: tt)*
     ) => {
-        parse_where_shim! {
-            { clause, preds },
-            then contract_fn!(
-                @after_where_generics,
-                [callback $($callback($($callback_args)*))*, double_check $double_check],
-                $(#[$attribute])* $(pub$(($access_modifier))*)* fn $fn_name,
-                {
-                    constr: [$($constr)*],
-                },
-                $args $( -> $return_type)*,
-            ),
-            where $($tail)*
+        $crate::contract_body! {
+            @processing_blocks
+            (pre_err $pre_err, pre $pre, old $old, callbacks [$($callback)* $(($name $($contract)*))+], decreases $decreases, body $body, post $return_value $post, post_variants $post_variants, double_check $double_check, unwind $unwind, context $context $(, #![$inner_attribute])*)
+            $($tail)*
         }
     };
     (
-        @after_bracket_generics,
-        [callback $($callback: ident ($($callback_args: tt)*))*, double_check $double_check: tt],
-        $(#[$attribute: meta])*
-        $(pub$(($access_modifier: ident))*)* fn $fn_name: ident,
-        {
-            constr: [$($constr: tt)*],
-        },
-        $args: tt $( -> $return_type: ty)*
-        {
-            $($block: tt)*
-        }
+        @processing_blocks
+        (pre_err $pre_err: tt, pre {}, old $old: tt, callbacks $callbacks: tt, decreases $decreases: tt, body $body: tt, post $return_value: tt $post: tt, post_variants $post_variants: tt, double_check $double_check: tt, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        pre {$($pre: tt)*}
         $($tail: tt)*
     ) => {
-        contract_fn! {
-            @after_where_generics,
-            [callback $($callback($($callback_args)*))*, double_check $double_check],
-            $(#[$attribute])*
-            $(pub$(($access_modifier))*)* fn $fn_name,
-            {
-                constr: [$($constr)*],
-            },
-            $args $( -> $return_type)*,
-            {
-                clause: [],
-                preds: [],
-            },
-            {
-                $($block)*
-            }
+        $crate::contract_body! {
+            @processing_blocks
+            (pre_err $pre_err, pre {$($pre)*}, old $old, callbacks $callbacks, decreases $decreases, body $body, post $return_value $post, post_variants $post_variants, double_check $double_check, unwind $unwind, context $context $(, #![$inner_attribute])*)
             $($tail)*
         }
     };
     (
-        @after_where_generics,
-        [callback $($callback: ident ($($callback_args: tt)*))*, double_check $double_check: tt],
-        $(#[$attribute: meta])*
-        $(pub$(($access_modifier: ident))*)* fn $fn_name: ident,
-        {
-            constr: [$($constr: tt)*],
-        },
-        $args: tt $( -> $return_type: ty)*,
-        {
-            clause: [$($where_clause: tt)*],
-            preds: $preds: tt,
-        },
-        {
-            $($block: tt)*
-        }
+        @processing_blocks
+        (pre_err $pre_err: tt, pre $pre: tt, old $old: tt, callbacks $callbacks: tt, decreases {}, body $body: tt, post $return_value: tt $post: tt, post_variants $post_variants: tt, double_check $double_check: tt, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        decreases {$($decreases: tt)*}
         $($tail: tt)*
     ) => {
-        $(#[$attribute])*
-        $(pub$(($access_modifier))*)* fn $fn_name <$($constr)*> $args $( -> $return_type )* $($where_clause)* {
-            contract_body! {
-                (pre {}, body {}, post (_def) {}, double_check {}, global_double_check $double_check)
-                $($block)*
-            }
-        }
-        contract_fn!{
-            @callback,
-            [$($callback($($callback_args)*))*],
+        $crate::contract_body! {
+            @processing_blocks
+            (pre_err $pre_err, pre $pre, old $old, callbacks $callbacks, decreases {$($decreases)*}, body $body, post $return_value $post, post_variants $post_variants, double_check $double_check, unwind $unwind, context $context $(, #![$inner_attribute])*)
             $($tail)*
         }
     };
     (
-        @callback,
-        [],
+        @processing_blocks
+        (pre_err $pre_err: tt, pre $pre: tt, old {}, callbacks $callbacks: tt, decreases $decreases: tt, body $body: tt, post $return_value: tt $post: tt, post_variants $post_variants: tt, double_check $double_check: tt, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        old {$($old: tt)*}
         $($tail: tt)*
-    ) => {};
+    ) => {
+        $crate::contract_body! {
+            @processing_blocks
+            (pre_err $pre_err, pre $pre, old {$($old)*}, callbacks $callbacks, decreases $decreases, body $body, post $return_value $post, post_variants $post_variants, double_check $double_check, unwind $unwind, context $context $(, #![$inner_attribute])*)
+            $($tail)*
+        }
+    };
     (
-        @callback,
-        [$callback: ident ($($args: tt)*)],
+        @processing_blocks
+        (pre_err $pre_err: tt, pre $pre: tt, old $old: tt, callbacks $callbacks: tt, decreases $decreases: tt, body {}, post $return_value: tt $post: tt, post_variants $post_variants: tt, double_check $double_check: tt, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        body {$($body: tt)*}
         $($tail: tt)*
     ) => {
-        $callback!{ $($args)* $($tail)* }
+        $crate::contract_body! {
+            @processing_blocks
+            (pre_err $pre_err, pre $pre, old $old, callbacks $callbacks, decreases $decreases, body {$($body)*}, post $return_value $post, post_variants $post_variants, double_check $double_check, unwind $unwind, context $context $(, #![$inner_attribute])*)
+            $($tail)*
+        }
     };
-}
-
-#[doc(hidden)]
-#[macro_export]
-macro_rules! contract_body {
     (
-        ($($blocks: tt)*)
-        #![$inner_attribute: meta]
+        @processing_blocks
+        (pre_err $pre_err: tt, pre $pre: tt, old $old: tt, callbacks $callbacks: tt, decreases $decreases: tt, body $body: tt, post $old_return_value: tt {}, post_variants $post_variants: tt, double_check $double_check: tt, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        post ($return_value: ident) {$($post: tt)*}
         $($tail: tt)*
     ) => {
-        contract_body! {
+        $crate::contract_body! {
             @processing_blocks
-            ($($blocks)*, #![$inner_attribute])
+            (pre_err $pre_err, pre $pre, old $old, callbacks $callbacks, decreases $decreases, body $body, post (binding $return_value) {$($post)*}, post_variants $post_variants, double_check $double_check, unwind $unwind, context $context $(, #![$inner_attribute])*)
             $($tail)*
         }
     };
     (
-        ($($blocks: tt)*)
+        @processing_blocks
+        (pre_err $pre_err: tt, pre $pre: tt, old $old: tt, callbacks $callbacks: tt, decreases $decreases: tt, body $body: tt, post $old_return_value: tt {}, post_variants $post_variants: tt, double_check $double_check: tt, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        post ($pattern: pat) {$($post: tt)*}
         $($tail: tt)*
     ) => {
-        contract_body! {
+        $crate::contract_body! {
             @processing_blocks
-            ($($blocks)*)
+            (pre_err $pre_err, pre $pre, old $old, callbacks $callbacks, decreases $decreases, body $body, post (pattern $pattern) {$($post)*}, post_variants $post_variants, double_check $double_check, unwind $unwind, context $context $(, #![$inner_attribute])*)
             $($tail)*
         }
     };
     (
         @processing_blocks
-        (pre {}, body $body: tt, post ($return_value: ident) $post: tt, double_check $double_check: tt, global_double_check $global_double_check: tt $(, #![$inner_attribute: meta])*)
-        pre $pre: tt
+        (pre_err $pre_err: tt, pre $pre: tt, old $old: tt, callbacks $callbacks: tt, decreases $decreases: tt, body $body: tt, post $return_value: tt {}, post_variants $post_variants: tt, double_check $double_check: tt, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        post {$($post: tt)*}
         $($tail: tt)*
     ) => {
-        contract_body! {
+        $crate::contract_body! {
             @processing_blocks
-            (pre $pre, body $body, post ($return_value) $post, double_check $double_check, global_double_check $global_double_check $(, #![$inner_attribute])*)
+            (pre_err $pre_err, pre $pre, old $old, callbacks $callbacks, decreases $decreases, body $body, post $return_value {$($post)*}, post_variants $post_variants, double_check $double_check, unwind $unwind, context $context $(, #![$inner_attribute])*)
             $($tail)*
         }
     };
     (
         @processing_blocks
-        (pre $pre: tt, body {}, post ($return_value: ident) $post: tt, double_check $double_check: tt, global_double_check $global_double_check: tt $(, #![$inner_attribute: meta])*)
-        body $body: tt
+        (pre_err $pre_err: tt, pre $pre: tt, old $old: tt, callbacks $callbacks: tt, decreases $decreases: tt, body $body: tt, post $return_value: tt $post: tt, post_variants [$($post_variant: tt)*], double_check $double_check: tt, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        @post_variant $pattern: tt {$($block: tt)*}
         $($tail: tt)*
     ) => {
-        contract_body! {
+        $crate::contract_body! {
             @processing_blocks
-            (pre $pre, body $body, post ($return_value) $post, double_check $double_check, global_double_check $global_double_check $(, #![$inner_attribute])*)
+            (pre_err $pre_err, pre $pre, old $old, callbacks $callbacks, decreases $decreases, body $body, post $return_value $post, post_variants [$($post_variant)* ($pattern {$($block)*})], double_check $double_check, unwind $unwind, context $context $(, #![$inner_attribute])*)
             $($tail)*
         }
     };
     (
         @processing_blocks
-        (pre $pre: tt, body $body: tt, post ($old_return_value: ident) {}, double_check $double_check: tt, global_double_check $global_double_check: tt $(, #![$inner_attribute: meta])*)
-        post ($return_value: ident) $post: tt
+        (pre_err $pre_err: tt, pre $pre: tt, old $old: tt, callbacks $callbacks: tt, decreases $decreases: tt, body $body: tt, post $return_value: tt $post: tt, post_variants $post_variants: tt, double_check {}, unwind $unwind: tt, context $context: tt $(, #![$inner_attribute: meta])*)
+        double_check {$($double_check: tt)*}
         $($tail: tt)*
     ) => {
-        contract_body! {
+        $crate::contract_body! {
             @processing_blocks
-            (pre $pre, body $body, post ($return_value) $post, double_check $double_check, global_double_check $global_double_check $(, #![$inner_attribute])*)
+            (pre_err $pre_err, pre $pre, old $old, callbacks $callbacks, decreases $decreases, body $body, post $return_value $post, post_variants $post_variants, double_check {$($double_check)*}, unwind $unwind, context $context $(, #![$inner_attribute])*)
             $($tail)*
         }
     };
     (
         @processing_blocks
-        (pre $pre: tt, body $body: tt, post ($return_value: ident) {}, double_check $double_check: tt, global_double_check $global_double_check: tt $(, #![$inner_attribute: meta])*)
-        post $post: tt
+        (pre_err $pre_err: tt, pre $pre: tt, old $old: tt, callbacks $callbacks: tt, decreases $decreases: tt, body $body: tt, post $return_value: tt $post: tt, post_variants $post_variants: tt, double_check {}, unwind [$($unwind: tt)*], context $context: tt $(, #![$inner_attribute: meta])*)
+        double_check (unwind) {$($double_check: tt)*}
         $($tail: tt)*
     ) => {
-        contract_body! {
+        $crate::contract_body! {
             @processing_blocks
-            (pre $pre, body $body, post ($return_value) $post, double_check $double_check, global_double_check $global_double_check $(, #![$inner_attribute])*)
+            (pre_err $pre_err, pre $pre, old $old, callbacks $callbacks, decreases $decreases, body $body, post $return_value $post, post_variants $post_variants, double_check {$($double_check)*}, unwind [$($unwind)* double_check DoubleCheck {$($double_check)*}], context $context $(, #![$inner_attribute])*)
             $($tail)*
         }
     };
     (
         @processing_blocks
-        (pre $pre: tt, body $body: tt, post ($return_value: ident) $post: tt, double_check {}, global_double_check $global_double_check: tt $(, #![$inner_attribute: meta])*)
-        double_check $double_check: tt
+        (pre_err $pre_err: tt, pre $pre: tt, old $old: tt, callbacks $callbacks: tt, decreases $decreases: tt, body $body: tt, post $return_value: tt $post: tt, post_variants $post_variants: tt, double_check $double_check: tt, unwind [$($unwind: tt)*], context $context: tt $(, #![$inner_attribute: meta])*)
+        on_panic {$($on_panic: tt)*}
         $($tail: tt)*
     ) => {
-        contract_body! {
+        $crate::contract_body! {
             @processing_blocks
-            (pre $pre, body $body, post ($return_value) $post, double_check $double_check, global_double_check $global_double_check $(, #![$inner_attribute])*)
+            (pre_err $pre_err, pre $pre, old $old, callbacks $callbacks, decreases $decreases, body $body, post $return_value $post, post_variants $post_variants, double_check $double_check, unwind [$($unwind)* on_panic OnPanic {$($on_panic)*}], context $context $(, #![$inner_attribute])*)
             $($tail)*
         }
     };
     (
         @processing_blocks
-        (pre $pre: tt, body $body: tt, post ($return_value: ident) $post: tt, double_check $double_check: tt, global_double_check $global_double_check: tt $(, #![$inner_attribute: meta])*)
+        (pre_err $pre_err: tt, pre $pre: tt, old {$($old: tt)*}, callbacks [$($callback: tt)*], decreases $decreases: tt, body $body: tt, post $return_value: tt $post: tt, post_variants $post_variants: tt, double_check $double_check: tt, unwind $unwind: tt, context (fn_name $fn_name: ident, global_double_check $global_double_check: tt, mode $mode: ident $(, global (unwind $global_unwind: ident, outermost $outermost: ident, reentrancy_guard $reentrancy_guard: ident, object $object: tt))?) $(, #![$inner_attribute: meta])*)
     ) => {
         {
             $(#![$inner_attribute])*
 
-            $pre
+            $crate::contract_body!(@pre_err $mode, $fn_name, $pre_err);
 
-            $global_double_check
+            $crate::contract_body!(@enter __adhesion_call $(, $object)?);
 
-            $double_check
+            $crate::contract_body!(@reentrancy_guard $mode, $fn_name, __adhesion_call $(, $reentrancy_guard)?);
 
-            let $return_value = $body;
+            if $crate::contract_body!(@enabled pre) {
+                $crate::contract_body!(@check $mode, Pre, $fn_name, $pre)
+            }
 
-            $global_double_check
+            $crate::contract_body!(@decreases $mode, $fn_name, __adhesion_measure, $decreases);
 
-            $double_check
+            if $crate::contract_body!(@enabled (global_double_check __adhesion_call $(, $outermost)?)) {
+                $crate::contract_body!(@check $mode, GlobalDoubleCheck, $fn_name, $global_double_check)
+            }
+
+            if $crate::contract_body!(@enabled double_check) {
+                $crate::contract_body!(@check $mode, DoubleCheck, $fn_name, $double_check)
+            }
 
-            $post
+            // Bindings made in `old` need to outlive `body` so that `post` can
+            // see them, so this block gets spliced in without its braces.
+            $($old)*
+
+            $($crate::contract_body!(@callback $fn_name, $callback);)*
+
+            let __adhesion_return_value = $crate::contract_body!(
+                @evaluate_unwinding $mode, $fn_name, $body,
+                [$($global_unwind)?], (global_double_check __adhesion_call $(, $outermost)?), $global_double_check,
+                $unwind
+            );
+
+            if $crate::contract_body!(@enabled (global_double_check __adhesion_call $(, $outermost)?)) {
+                $crate::contract_body!(@check $mode, GlobalDoubleCheck, $fn_name, $global_double_check)
+            }
+
+            if $crate::contract_body!(@enabled double_check) {
+                $crate::contract_body!(@check $mode, DoubleCheck, $fn_name, $double_check)
+            }
+
+            $crate::contract_body!(@post $mode, $fn_name, $return_value, $post, $post_variants, __adhesion_return_value)
+        }
+    };
+    // `post_ok` and the like are `post` blocks that only run for one variant of
+    // the return value. Any number of them can be used, and they're run in the
+    // order they were written.
+    (@processing_blocks $state: tt post_ok ($pattern: pat) {$($block: tt)*} $($tail: tt)*) => {
+        $crate::contract_body! { @processing_blocks $state @post_variant [::core::result::Result::Ok($pattern)] {$($block)*} $($tail)* }
+    };
+    (@processing_blocks $state: tt post_ok {$($block: tt)*} $($tail: tt)*) => {
+        $crate::contract_body! { @processing_blocks $state @post_variant [::core::result::Result::Ok(_)] {$($block)*} $($tail)* }
+    };
+    (@processing_blocks $state: tt post_err ($pattern: pat) {$($block: tt)*} $($tail: tt)*) => {
+        $crate::contract_body! { @processing_blocks $state @post_variant [::core::result::Result::Err($pattern)] {$($block)*} $($tail)* }
+    };
+    (@processing_blocks $state: tt post_err {$($block: tt)*} $($tail: tt)*) => {
+        $crate::contract_body! { @processing_blocks $state @post_variant [::core::result::Result::Err(_)] {$($block)*} $($tail)* }
+    };
+    (@processing_blocks $state: tt post_some ($pattern: pat) {$($block: tt)*} $($tail: tt)*) => {
+        $crate::contract_body! { @processing_blocks $state @post_variant [::core::option::Option::Some($pattern)] {$($block)*} $($tail)* }
+    };
+    (@processing_blocks $state: tt post_some {$($block: tt)*} $($tail: tt)*) => {
+        $crate::contract_body! { @processing_blocks $state @post_variant [::core::option::Option::Some(_)] {$($block)*} $($tail)* }
+    };
+    (@processing_blocks $state: tt post_none {$($block: tt)*} $($tail: tt)*) => {
+        $crate::contract_body! { @processing_blocks $state @post_variant [::core::option::Option::None] {$($block)*} $($tail)* }
+    };
+    // Malformed blocks never get here, since the front ends in
+    // `adhesion-macros` report them at the block that's wrong.
+    // Diverging after the error keeps it from being followed by a confusing
+    // type error about the body of the `fn`.
+    (@error $message: expr) => {
+        {
+            compile_error!($message);
+            unreachable!()
+        }
+    };
+    // Evaluating `body` inside of a closure or an `async` block means that
+    // `return` and `?` only exit that, so the checks after it run on every exit
+    // path. Neither can be used in a `const fn`, so `body` is evaluated as-is
+    // there.
+    (@evaluate normal, $body: tt) => {
+        $crate::__private::evaluate(|| $body)
+    };
+    (@evaluate async_fn, $body: tt) => {
+        async $body.await
+    };
+    (@evaluate const_fn, $body: tt) => {
+        $body
+    };
+    // `double_check(outermost)` and `reentrancy_guard` need to know whether a
+    // call is the outermost one into an object, which is tracked by `call`. It
+    // lives for the whole call, so that the object is left again however the
+    // call ends.
+    (@enter $call: ident) => {};
+    (@enter $call: ident, {}) => {};
+    (@enter $call: ident, {$($object: tt)+}) => {
+        let $call = $crate::__private::Call::enter($($object)+);
+    };
+    (@outermost $call: ident) => {
+        true
+    };
+    (@outermost $call: ident, false) => {
+        true
+    };
+    (@outermost $call: ident, true) => {
+        $call.is_outermost()
+    };
+    (@reentrancy_guard $mode: ident, $fn_name: ident, $call: ident) => {};
+    (@reentrancy_guard $mode: ident, $fn_name: ident, $call: ident, false) => {};
+    (@reentrancy_guard $mode: ident, $fn_name: ident, $call: ident, true) => {
+        if $crate::contract_body!(@enabled build) && !$call.is_outermost() {
+            $crate::__private::report($crate::__private::violation(
+                $crate::ViolationKind::Reentrancy,
+                stringify!($fn_name),
+                "no other contracted call on the same object is running",
+                file!(),
+                line!(),
+                None,
+                None,
+            ));
+        }
+    };
+    // The measure of a recursive `fn` is kept on a stack for the thread for as
+    // long as the call runs, so that the calls it makes into itself can be
+    // compared against it. Every `fn` keeps its own measures apart by the
+    // address of a `static` of its own. `const` and `async` `fn`s, which can't
+    // do this, are turned away by the front ends.
+    (@decreases $mode: ident, $fn_name: ident, $measure: ident, {}) => {};
+    (@decreases $mode: ident, $fn_name: ident, $measure: ident, {$decreases: expr}) => {
+        let $measure = if $crate::contract_body!(@enabled build) {
+            static __ADHESION_FN: u8 = 0;
+            let measure = $decreases;
+            if let Some(problem) = $crate::__private::Measure::check(&__ADHESION_FN, &measure) {
+                $crate::__private::report($crate::__private::violation(
+                    $crate::ViolationKind::Decreases,
+                    stringify!($fn_name),
+                    stringify!($decreases),
+                    file!(),
+                    line!(),
+                    Some(String::from(problem)),
+                    None,
+                ));
+            }
+            Some($crate::__private::Measure::enter(&__ADHESION_FN, measure))
+        } else {
+            None
+        };
+    };
+    (@decreases $mode: ident, $fn_name: ident, $measure: ident, $decreases: tt) => {
+        $crate::contract_body!(@error "adhesion: a `decreases` block has to be a single expression")
+    };
+    // Checks that run when `body` panics come from `on_panic` blocks and from
+    // `double_check(unwind)`, at the top level or in the `fn`. The panic is
+    // caught and resumed afterwards, since a drop guard couldn't borrow what
+    // `body` borrows, and a violation reported from it while unwinding would
+    // abort the process instead of being reported.
+    (@evaluate_unwinding $mode: ident, $fn_name: ident, $body: tt, [true], $global: tt, $global_double_check: tt, [$($unwind: tt)*]) => {
+        $crate::contract_body!(
+            @evaluate_unwinding $mode, $fn_name, $body, [], $global, $global_double_check,
+            [$global GlobalDoubleCheck $global_double_check $($unwind)*]
+        )
+    };
+    (@evaluate_unwinding $mode: ident, $fn_name: ident, $body: tt, [$($_false: ident)?], $global: tt, $global_double_check: tt, []) => {
+        $crate::contract_body!(@evaluate $mode, $body)
+    };
+    (@evaluate_unwinding const_fn, $($rest: tt)*) => {
+        $crate::contract_body!(@error "adhesion: a `const fn` can't catch panics, so it can't use `on_panic` or `double_check(unwind)`")
+    };
+    (@evaluate_unwinding $mode: ident, $fn_name: ident, $body: tt, [$($_false: ident)?], $global: tt, $global_double_check: tt, [$($enabled: tt $kind: ident $block: tt)*]) => {
+        match $crate::contract_body!(@catch_unwind $mode, $body) {
+            ::core::result::Result::Ok(value) => value,
+            ::core::result::Result::Err(payload) => {
+                $(
+                    if $crate::contract_body!(@enabled $enabled) {
+                        $crate::contract_body!(@check $mode, $kind, $fn_name, $block)
+                    }
+                )*
+                $crate::__private::resume_unwind(payload)
+            }
+        }
+    };
+    (@catch_unwind normal, $body: tt) => {
+        $crate::__private::catch_unwind(|| $body)
+    };
+    (@catch_unwind async_fn, $body: tt) => {
+        $crate::__private::CatchUnwind::new(async $body).await
+    };
+    // A callback argument is shadowed by one that checks its contract around
+    // every call, after `old`, so that nothing before `body` sees the change.
+    // The checks borrow whatever they use from the contracted `fn`, like
+    // its other arguments.
+    (@callback $fn_name: ident, ($callback: ident |$($argument: ident),* $(,)?| $(pre {$($pre: tt)*})? $(post $(($return_value: ident))? {$($post: tt)*})?)) => {
+        let $callback = $crate::contract_body!(@callback_wrapper [$($argument)*])(
+            $callback,
+            |$callback, $($argument),*| {
+                $(
+                    if $crate::contract_body!(@enabled pre) {
+                        $crate::contract_body!(@check normal, CallbackPre, $fn_name, {$($pre)*})
+                    }
+                )?
+                let __adhesion_return_value = $callback($($argument),*);
+                $(
+                    $(let $return_value = __adhesion_return_value;)?
+                    if $crate::contract_body!(@enabled post) {
+                        $crate::contract_body!(@check normal, CallbackPost, $fn_name, {$($post)*})
+                    }
+                    $(let __adhesion_return_value = $return_value;)?
+                )?
+                __adhesion_return_value
+            },
+        );
+    };
+    // `||` is a single token, so a callback without arguments needs its own
+    // rule.
+    (@callback $fn_name: ident, ($callback: ident || $($contract: tt)*)) => {
+        $crate::contract_body!(@callback $fn_name, ($callback | | $($contract)*));
+    };
+    (@callback $fn_name: ident, ($callback: ident $($contract: tt)*)) => {
+        $crate::contract_body!(@error concat!(
+            "adhesion: expected a `fn_contract!` like `fn_contract!(|x| pre { ... } post(r) { ... })` for `",
+            stringify!($callback),
+            "`"
+        ))
+    };
+    (@callback_wrapper []) => { $crate::__private::callback_0 };
+    (@callback_wrapper [$a: ident]) => { $crate::__private::callback_1 };
+    (@callback_wrapper [$a: ident $b: ident]) => { $crate::__private::callback_2 };
+    (@callback_wrapper [$a: ident $b: ident $c: ident]) => { $crate::__private::callback_3 };
+    (@callback_wrapper [$a: ident $b: ident $c: ident $d: ident]) => { $crate::__private::callback_4 };
+    (@callback_wrapper [$($argument: ident)*]) => {
+        $crate::contract_body!(@error "adhesion: a callback with a `fn_contract!` may take at most 4 arguments")
+    };
+    // A single name takes the return value by value, as it always has. Any
+    // other pattern is matched against it like in a `match`, so a refutable
+    // one only checks the return values that it matches.
+    (@post $mode: ident, $fn_name: ident, (binding $return_value: ident), $post: tt, $post_variants: tt, $value: ident) => {
+        {
+            let $return_value = $value;
+
+            if $crate::contract_body!(@enabled post) {
+                $crate::contract_body!(@check $mode, Post, $fn_name, $post)
+            }
+
+            $crate::contract_body!(@post_variants $mode, $fn_name, $post_variants, &$return_value);
 
             $return_value
         }
     };
+    (@post $mode: ident, $fn_name: ident, (pattern $pattern: pat), $post: tt, $post_variants: tt, $value: ident) => {
+        {
+            #[allow(unreachable_patterns)]
+            if $crate::contract_body!(@enabled post) {
+                match $value {
+                    $pattern => $crate::contract_body!(@check $mode, Post, $fn_name, $post),
+                    _ => (),
+                }
+            }
+
+            $crate::contract_body!(@post_variants $mode, $fn_name, $post_variants, &$value);
+
+            $value
+        }
+    };
+    // The variant blocks match a reference to the return value, so their
+    // bindings are references too, and never move anything out of it.
+    (@post_variants $mode: ident, $fn_name: ident, [$(([$($pattern: tt)*] $block: tt))*], $value: expr) => {
+        if $crate::contract_body!(@enabled post) {
+            $(
+                #[allow(irrefutable_let_patterns)]
+                if let $($pattern)* = $value {
+                    $crate::contract_body!(@check $mode, Post, $fn_name, $block)
+                }
+            )*
+        }
+    };
+    // Unlike other checks, `pre_err` isn't reported as a violation, since its
+    // failures are returned to the caller. A missing error is made from a
+    // `ContractViolation`, like `?` would.
+    (@pre_err $mode: ident, $fn_name: ident, {$($condition: expr $(=> $error: expr)?),* $(,)?}) => {
+        $(
+            if !$condition {
+                return ::core::result::Result::Err(
+                    $crate::contract_body!(@pre_error $mode, $fn_name, $condition $(, $error)?)
+                );
+            }
+        )*
+    };
+    (@pre_error const_fn, $fn_name: ident, $condition: expr, $error: expr) => {
+        $error
+    };
+    (@pre_error $mode: ident, $fn_name: ident, $condition: expr, $error: expr) => {
+        ::core::convert::From::from($error)
+    };
+    (@pre_error $mode: ident, $fn_name: ident, $condition: expr) => {
+        ::core::convert::From::from($crate::__private::violation(
+            $crate::ViolationKind::Pre,
+            stringify!($fn_name),
+            stringify!($condition),
+            file!(),
+            line!(),
+            None,
+            None,
+        ))
+    };
+    // A check block is either made of statements, which are run as-is, or is
+    // a single condition with an optional message, like `{ x > 0, "message" }`.
+    // Telling them apart takes a parser rather than a token muncher, which
+    // would recurse once per token of the block.
+    // Besides the modes of a `fn`, `mode` may be `(iteration $iteration)` in a
+    // `contract_loop!`, whose violations report the iteration they're on.
+    (@check $mode: tt, $kind: ident, $fn_name: ident, $block: tt) => {
+        $crate::__private::__check!($crate; $mode, $kind, $fn_name, $block)
+    };
+    // Neither trait methods nor the violation handler can be called from a
+    // `const fn`, so conditions there have to be `bool`s that panic directly.
+    (@condition const_fn, $kind: ident, $fn_name: ident, $condition: expr, []) => {
+        if !$condition {
+            panic!(concat!(stringify!($kind), " check violated in `", stringify!($fn_name), "`: `", stringify!($condition), "`"));
+        }
+    };
+    (@condition const_fn, $kind: ident, $fn_name: ident, $condition: expr, [$($message: expr),+]) => {
+        if !$condition {
+            panic!($($message),+);
+        }
+    };
+    (@condition (iteration $iteration: ident), $kind: ident, $fn_name: ident, $condition: expr, [$($message: expr),*]) => {
+        if !$crate::__private::Condition::holds($condition) {
+            $crate::__private::report($crate::__private::violation(
+                $crate::ViolationKind::$kind,
+                stringify!($fn_name),
+                stringify!($condition),
+                file!(),
+                line!(),
+                $crate::contract_body!(@message $($message),*),
+                Some($iteration),
+            ));
+        }
+    };
+    (@condition $mode: ident, $kind: ident, $fn_name: ident, $condition: expr, [$($message: expr),*]) => {
+        if !$crate::__private::Condition::holds($condition) {
+            $crate::__private::report($crate::__private::violation(
+                $crate::ViolationKind::$kind,
+                stringify!($fn_name),
+                stringify!($condition),
+                file!(),
+                line!(),
+                $crate::contract_body!(@message $($message),*),
+                None,
+            ));
+        }
+    };
+    (@message) => {
+        None
+    };
+    (@message $($message: expr),+) => {
+        Some(format!($($message),+))
+    };
+    // `cfg!(debug_assertions)` has to be expanded in the crate that contains the
+    // contract, so that it follows that crate's build profile.
+    (@enabled pre) => {
+        $crate::__private::PRE_ENABLED && $crate::contract_body!(@enabled build)
+    };
+    (@enabled post) => {
+        $crate::__private::POST_ENABLED && $crate::contract_body!(@enabled build)
+    };
+    (@enabled double_check) => {
+        $crate::__private::DOUBLE_CHECK_ENABLED && $crate::contract_body!(@enabled build)
+    };
+    (@enabled on_panic) => {
+        $crate::contract_body!(@enabled build)
+    };
+    (@enabled (global_double_check $call: ident $(, $outermost: ident)?)) => {
+        $crate::contract_body!(@enabled double_check) && $crate::contract_body!(@outermost $call $(, $outermost)?)
+    };
+    (@enabled build) => {
+        (!$crate::__private::DEBUG_ONLY || cfg!(debug_assertions))
+    };
 }
@@ -0,0 +1,250 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::panic;
+use std::sync::{PoisonError, RwLock};
+
+/// The kind of check that a [`ContractViolation`](./struct.ContractViolation.html)
+/// came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ViolationKind {
+    /// A `pre` check, which means the caller broke the contract.
+    Pre,
+    /// A `post` check, which means the contracted `fn` broke the contract.
+    Post,
+    /// A `double_check` block that belongs to a single `fn`.
+    DoubleCheck,
+    /// A `double_check` block written at the top level of `contract!`, which
+    /// applies to every `fn` in it.
+    GlobalDoubleCheck,
+    /// An `on_panic` block, which runs when the body of a contracted `fn`
+    /// panics.
+    OnPanic,
+    /// A contracted method was called on an object while another contracted
+    /// method was still running on it, like through a callback, in a
+    /// `contract!` with a `reentrancy_guard`.
+    Reentrancy,
+    /// A `pre` block of a `fn_contract!`, which means the contracted `fn`
+    /// called a callback argument with input that breaks its contract.
+    CallbackPre,
+    /// A `post` block of a `fn_contract!`, which means a callback argument
+    /// returned something that breaks its contract, so whoever passed it to
+    /// the contracted `fn` is at fault.
+    CallbackPost,
+    /// An `invariant` block of a `contract_loop!`, which is checked before
+    /// every iteration.
+    LoopInvariant,
+    /// A `decreases` block of a `contract_loop!`, whose value either went
+    /// below zero or didn't decrease since the previous iteration.
+    LoopVariant,
+    /// A `decreases` block of a contracted `fn`, whose value either went below
+    /// zero or didn't decrease since the call into the same `fn` that it was
+    /// made from.
+    Decreases,
+    /// An `#[invariant(...)]` attribute of a type with a derived `Invariant`.
+    Invariant,
+}
+
+impl ViolationKind {
+    /// Which side of a call into the contracted `fn` broke its contract.
+    ///
+    /// A callback with a `fn_contract!` counts as part of the caller that
+    /// passed it in, so a bad return value from it blames the caller, while a
+    /// bad input to it blames the contracted `fn` that called it.
+    pub fn blame(self) -> Blame {
+        match self {
+            ViolationKind::Pre | ViolationKind::Reentrancy | ViolationKind::CallbackPost => {
+                Blame::Caller
+            }
+            ViolationKind::Post
+            | ViolationKind::DoubleCheck
+            | ViolationKind::GlobalDoubleCheck
+            | ViolationKind::OnPanic
+            | ViolationKind::CallbackPre
+            | ViolationKind::LoopInvariant
+            | ViolationKind::LoopVariant
+            | ViolationKind::Decreases
+            | ViolationKind::Invariant => Blame::Callee,
+        }
+    }
+}
+
+/// The party that's at fault for a [`ContractViolation`](./struct.ContractViolation.html),
+/// as given by [`ViolationKind::blame`](./enum.ViolationKind.html#method.blame).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Blame {
+    /// Whoever called the contracted `fn`, including any callbacks that it
+    /// passed in.
+    Caller,
+    /// The contracted `fn` itself.
+    Callee,
+}
+
+impl Display for Blame {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Blame::Caller => "caller",
+            Blame::Callee => "callee",
+        })
+    }
+}
+
+impl Display for ViolationKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ViolationKind::Pre => "pre-condition",
+            ViolationKind::Post => "post-condition",
+            ViolationKind::DoubleCheck => "double_check",
+            ViolationKind::GlobalDoubleCheck => "global double_check",
+            ViolationKind::OnPanic => "on_panic",
+            ViolationKind::Reentrancy => "reentrancy guard",
+            ViolationKind::CallbackPre => "callback pre-condition",
+            ViolationKind::CallbackPost => "callback post-condition",
+            ViolationKind::LoopInvariant => "loop invariant",
+            ViolationKind::LoopVariant => "loop variant",
+            ViolationKind::Decreases => "decreases",
+            ViolationKind::Invariant => "invariant",
+        })
+    }
+}
+
+/// A report of a failed check written in condition form, i.e.,
+/// `pre { x > 0, "message" }`, which is passed to the
+/// [violation handler](./fn.set_violation_handler.html). By default, a
+/// violation panics with this as its payload, so it can be recovered with
+/// `downcast_ref` from the result of `std::panic::catch_unwind`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ContractViolation {
+    /// Which kind of check failed.
+    pub kind: ViolationKind,
+    /// The name of the contracted `fn`, the keyword of the loop in a
+    /// `contract_loop!`, like `while`, or the name of the type whose derived
+    /// `Invariant` failed.
+    pub fn_name: &'static str,
+    /// The source text of the condition that failed.
+    pub condition_text: &'static str,
+    /// The file where the contract was written.
+    pub file: &'static str,
+    /// The line where the contract was written.
+    pub line: u32,
+    /// The formatted message given after the condition, if any.
+    pub message: Option<String>,
+    /// The iteration of a `contract_loop!` that the check failed on, counting
+    /// from 1.
+    pub iteration: Option<usize>,
+}
+
+impl Display for ContractViolation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} violated in `{}` at {}:{}",
+            self.kind, self.fn_name, self.file, self.line
+        )?;
+        if let Some(iteration) = self.iteration {
+            write!(f, " on iteration {}", iteration)?;
+        }
+        write!(f, ": `{}`", self.condition_text)?;
+        if let Some(ref message) = self.message {
+            write!(f, ": {}", message)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ContractViolation {}
+
+static VIOLATION_HANDLER: RwLock<fn(&ContractViolation)> = RwLock::new(default_violation_handler);
+
+/// Sets the function that every failed condition is reported to, for the whole
+/// program. This replaces the previous handler, which starts out as
+/// [`default_violation_handler`](./fn.default_violation_handler.html).
+///
+/// A handler may panic to stop the contracted `fn` like the default one does,
+/// or it may return, e.g., after logging the violation or counting it. When a
+/// handler returns, the contracted `fn` carries on as if the check had passed.
+///
+/// Only checks written in condition form are reported this way; see
+/// [`contract!`](./macro.contract.html).
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate adhesion;
+/// #
+/// # fn main () {
+/// use adhesion::ContractViolation;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static VIOLATIONS: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn count(violation: &ContractViolation) {
+///     eprintln!("{}", violation);
+///     VIOLATIONS.fetch_add(1, Ordering::SeqCst);
+/// }
+///
+/// contract! {
+///     fn double(x: u32) -> u32 {
+///         pre {
+///             x < 1000
+///         }
+///         body {
+///             x.saturating_mul(2)
+///         }
+///     }
+/// }
+///
+/// adhesion::set_violation_handler(count);
+/// assert!(double(5000) == 10000);
+/// assert!(VIOLATIONS.load(Ordering::SeqCst) == 1);
+/// # }
+/// ```
+pub fn set_violation_handler(handler: fn(&ContractViolation)) {
+    *VIOLATION_HANDLER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = handler;
+}
+
+/// The violation handler that is used until
+/// [`set_violation_handler`](./fn.set_violation_handler.html) is called. It
+/// prints `violation` to stderr and then panics with a copy of it as the
+/// payload, which can be recovered with `downcast_ref` from the result of
+/// `std::panic::catch_unwind`.
+pub fn default_violation_handler(violation: &ContractViolation) {
+    eprintln!("{}", violation);
+    panic::panic_any(violation.clone())
+}
+
+// Contracts are expanded in other crates, which can't build a
+// `ContractViolation` with a struct expression now that it's non-exhaustive.
+#[doc(hidden)]
+pub fn violation(
+    kind: ViolationKind,
+    fn_name: &'static str,
+    condition_text: &'static str,
+    file: &'static str,
+    line: u32,
+    message: Option<String>,
+    iteration: Option<usize>,
+) -> ContractViolation {
+    ContractViolation {
+        kind,
+        fn_name,
+        condition_text,
+        file,
+        line,
+        message,
+        iteration,
+    }
+}
+
+#[doc(hidden)]
+pub fn report(violation: ContractViolation) {
+    // The lock is released before calling the handler, so that a handler that
+    // panics doesn't poison it and one that sets another handler doesn't
+    // deadlock.
+    let handler = *VIOLATION_HANDLER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+    handler(&violation);
+}
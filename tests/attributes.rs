@@ -0,0 +1,136 @@
+extern crate adhesion;
+#[macro_use]
+extern crate galvanic_assert;
+
+use adhesion::{double_check, post, pre};
+use std::cell::RefCell;
+
+#[test]
+#[cfg_attr(
+    any(feature = "disable-pre", feature = "disable-post", feature = "disable-double-check"),
+    ignore = "asserts that checks panic"
+)]
+fn single_attributes() {
+    #[pre(x < 30, "pre-condition violation")]
+    fn only_pre(x: u32) -> u32 {
+        x
+    }
+
+    #[post(ret => ret % 2 == 0)]
+    fn only_post(x: u32) -> u32 {
+        x * 2 + (x == 3) as u32
+    }
+
+    #[double_check(*x > 5)]
+    fn only_double_check(x: &mut u32) {
+        *x -= 1;
+    }
+
+    only_pre(3);
+    assert_that!(only_pre(30), panics);
+    only_post(2);
+    assert_that!(only_post(3), panics);
+    only_double_check(&mut 7);
+    assert_that!(only_double_check(&mut 6), panics);
+    assert_that!(only_double_check(&mut 5), panics);
+}
+
+#[test]
+#[cfg_attr(
+    any(feature = "disable-pre", feature = "disable-post", feature = "disable-double-check"),
+    ignore = "asserts that checks panic"
+)]
+fn ordering_matches_contract() {
+    thread_local! {
+        static LOG: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn log(event: &'static str) -> bool {
+        LOG.with(|log| log.borrow_mut().push(event));
+        true
+    }
+
+    #[post(log("post"))]
+    #[double_check(log("double_check"))]
+    #[pre(log("pre"))]
+    #[post(ret => ret == 4 && log("post again"))]
+    fn logged(x: u32) -> u32 {
+        log("body");
+        if x == 3 {
+            return x + 1;
+        }
+        x
+    }
+
+    logged(3);
+    LOG.with(|log| {
+        assert_eq!(
+            *log.borrow(),
+            [
+                "pre",
+                "double_check",
+                "body",
+                "double_check",
+                "post",
+                "post again",
+            ]
+        );
+    });
+}
+
+#[test]
+fn methods_and_generics() {
+    struct Counter {
+        count: u32,
+        max: u32,
+    }
+
+    impl Counter {
+        #[pre(self.count < self.max, "counter is already at its max")]
+        #[double_check(self.count <= self.max)]
+        #[post(self.count > 0)]
+        fn increment(&mut self) {
+            self.count += 1;
+        }
+
+        #[adhesion::post(count => count <= self.max)]
+        fn count(&self) -> u32 {
+            self.count
+        }
+    }
+
+    let mut counter = Counter { count: 0, max: 2 };
+    counter.increment();
+    counter.increment();
+    assert!(counter.count() == 2);
+    assert_that!(Counter { count: 2, max: 2 }.increment(), panics);
+
+    #[pre(!items.is_empty())]
+    fn first<T>(items: &[T]) -> T
+    where
+        T: Clone + ::std::fmt::Debug,
+    {
+        items[0].clone()
+    }
+
+    assert!(first(&[1, 2]) == 1);
+    assert_that!(first::<u8>(&[]), panics);
+}
+
+#[test]
+fn qualified_fns() {
+    #[pre(x > 0, "zero has no predecessor")]
+    #[post(ret => ret < x)]
+    const fn predecessor(x: u32) -> u32 {
+        x - 1
+    }
+
+    #[pre(!ptr.is_null())]
+    unsafe extern "C" fn read(ptr: *const u8) -> u8 {
+        *ptr
+    }
+
+    const _: () = assert!(predecessor(3) == 2);
+    assert_that!(predecessor(0), panics);
+    assert!(unsafe { read(&5) } == 5);
+}
@@ -1,10 +1,71 @@
-#![recursion_limit="256"]
-
 #[macro_use]
 extern crate adhesion;
 #[macro_use]
 extern crate galvanic_assert;
 
+use adhesion::ContractViolation;
+use std::future::Future;
+use std::panic::{catch_unwind, UnwindSafe};
+use std::pin::{pin, Pin};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+/// Runs `f`, which is expected to panic, and returns the `ContractViolation`
+/// that it panicked with, or `None` if it panicked with something else.
+fn violation_of<T>(f: impl FnOnce() -> T + UnwindSafe) -> Option<ContractViolation> {
+    catch_unwind(f)
+        .err()
+        .expect("expected a panic")
+        .downcast_ref::<ContractViolation>()
+        .cloned()
+}
+
+/// Polls `future` once, which is expected to finish it.
+fn ready<F: Future>(future: F) -> F::Output {
+    match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => unreachable!(),
+    }
+}
+
+/// Polls `future` once on this thread and finishes it on another one, like an
+/// executor with a pool of threads might.
+fn finish_on_another_thread<F>(future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    let mut future = Box::pin(future);
+    let _ = future.as_mut().poll(&mut Context::from_waker(Waker::noop()));
+    thread::scope(|scope| {
+        scope
+            .spawn(move || loop {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+                    return output;
+                }
+            })
+            .join()
+            .unwrap()
+    })
+}
+
+/// A future that's pending the first time it's polled.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
 #[test]
 fn empty_fn() {
     contract! { fn asdf() {} }
@@ -12,6 +73,7 @@ fn empty_fn() {
 }
 
 #[test]
+#[allow(clippy::unused_unit)]
 fn most_basic() {
     contract! {
         fn asdf() {
@@ -179,7 +241,7 @@ fn structs() {
 
             pub fn increment(&mut self) {
                 pre {
-                    assert!(self.count != u64::max_value(), "cannot increment counter with max value");
+                    assert!(self.count != u64::MAX, "cannot increment counter with max value");
                 }
                 body {
                     self.count += 1;
@@ -209,7 +271,7 @@ fn structs() {
         }
     }
 
-    assert_that!(Counter{ count: u64::max_value() }.increment(), panics);
+    assert_that!(Counter{ count: u64::MAX }.increment(), panics);
     assert_that!(Counter{ count: 0 }.decrement(), panics);
     assert!(Counter::new().borrow_count() == &0);
     assert!(Counter::new().consume() == 0);
@@ -242,6 +304,157 @@ fn generics() {
     }
 
     assert!(add_together2(2, 4) == 6, "add impl broken (!?)");
+
+    contract! {
+        #[allow(clippy::multiple_bound_locations)]
+        fn longest<'input, 'other: 'input, 'tag, T: 'input + ?Sized>(
+            left: &'input T,
+            right: &'other T,
+            _tag: &'tag str,
+        ) -> &'input T
+        where
+            'tag: 'input + 'static,
+            T: AsRef<str> + 'other,
+        {
+            pre {
+                !left.as_ref().is_empty() || !right.as_ref().is_empty()
+            }
+            body {
+                if left.as_ref().len() >= right.as_ref().len() { left } else { right }
+            }
+        }
+    }
+
+    assert!(longest("abc", "de", "tag") == "abc");
+    assert!(longest("", "de", "tag") == "de");
+}
+
+#[test]
+#[cfg_attr(feature = "disable-pre", ignore = "asserts that checks panic")]
+fn generic_forms() {
+    use std::fmt::Debug;
+
+    contract! {
+        fn const_generic<const N: usize>(items: [u8; N]) -> usize {
+            pre {
+                N > 0
+            }
+            body {
+                items.len()
+            }
+            post(len) {
+                len == N
+            }
+        }
+    }
+
+    contract! {
+        fn const_and_type_generics<'a, T: Debug + 'a, const N: usize, const FLAG: bool>(items: &'a [T; N]) -> bool {
+            body {
+                FLAG && !items.is_empty()
+            }
+        }
+    }
+
+    contract! {
+        fn closure_bound<T, F: Fn(&T) -> bool>(item: T, predicate: F) -> bool {
+            body {
+                predicate(&item)
+            }
+        }
+    }
+
+    contract! {
+        fn higher_ranked<T, F: for<'x> Fn(&'x T) -> &'x T>(item: T, f: F) -> bool
+        where
+            T: PartialEq,
+        {
+            body {
+                f(&item) == &item
+            }
+        }
+    }
+
+    contract! {
+        fn fn_mut_bound<F: FnMut(u8)>(mut f: F) {
+            body {
+                f(1)
+            }
+        }
+    }
+
+    contract! {
+        fn fn_once_option<F: FnOnce() -> Option<u8>>(f: F) -> Option<u8> {
+            body {
+                f()
+            }
+        }
+    }
+
+    contract! {
+        fn assoc_type<I: Iterator<Item = u8>>(items: I) -> u32 {
+            body {
+                items.map(u32::from).sum()
+            }
+        }
+    }
+
+    contract! {
+        fn nested_assoc_type<I: IntoIterator<Item = Vec<u8>>>(items: I) -> usize {
+            body {
+                items.into_iter().map(|v| v.len()).sum()
+            }
+        }
+    }
+
+    contract! {
+        fn unsized_param<T: ?Sized + Debug>(item: &T) -> String {
+            body {
+                format!("{:?}", item)
+            }
+        }
+    }
+
+    contract! {
+        fn paths_in_bounds<T: ::std::clone::Clone + std::fmt::Debug>(item: T) -> T {
+            body {
+                item.clone()
+            }
+        }
+    }
+
+    contract! {
+        fn where_forms<'a, 'b, T, U, F, const N: usize>(left: &'a T, right: &'b [U; N], f: F) -> usize
+        where
+            'b: 'a,
+            T: ?Sized + Debug,
+            U: Clone,
+            Vec<U>: Debug,
+            [U; N]: Debug,
+            T::Owned: Debug,
+            T: ToOwned,
+            <T as ToOwned>::Owned: Clone,
+            for<'x> &'x T: Debug,
+            F: for<'x> Fn(&'x T, &[U]) -> usize,
+        {
+            body {
+                f(left, &right[..])
+            }
+        }
+    }
+
+    assert!(const_generic([1, 2, 3]) == 3);
+    assert_that!(const_generic([]), panics);
+    assert!(const_and_type_generics::<_, 2, true>(&[1, 2]));
+    assert!(closure_bound(3, |x| *x == 3));
+    assert!(higher_ranked(3, |x| x));
+    fn_mut_bound(|_| ());
+    assert!(fn_once_option(|| Some(1)) == Some(1));
+    assert!(assoc_type(vec![1, 2].into_iter()) == 3);
+    assert!(nested_assoc_type(vec![vec![1, 2], vec![3]]) == 3);
+    assert!(unsized_param("abc") == "\"abc\"");
+    assert!(paths_in_bounds(3) == 3);
+    assert!(where_forms("abc", &[1, 2], |left, right| left.len() + right.len()) == 5);
 }
 
 #[test]
@@ -258,6 +471,7 @@ fn visibility() {
 }
 
 #[test]
+#[cfg_attr(feature = "disable-double-check", ignore = "asserts that checks panic")]
 fn global_double_check() {
     contract!{
         double_check {
@@ -278,3 +492,1464 @@ fn global_double_check() {
     assert_that!(test2(), panics);
     assert_that!(test3(), panics);
 }
+
+#[test]
+#[cfg_attr(
+    any(feature = "disable-post", feature = "disable-double-check"),
+    ignore = "asserts that checks panic"
+)]
+fn early_exits_still_check() {
+    contract! {
+        fn early_return(x: u32) -> u32 {
+            body {
+                if x == 0 {
+                    return 0;
+                }
+                x + 1
+            }
+            post(y) {
+                assert!(y != 0, "post-condition violation");
+            }
+        }
+    }
+
+    assert_that!(early_return(0), panics);
+    assert!(early_return(1) == 2);
+
+    contract! {
+        fn question_mark(s: &str) -> Result<u32, ::std::num::ParseIntError> {
+            body {
+                let parsed: u32 = s.parse()?;
+                Ok(parsed)
+            }
+            double_check {
+                assert!(!s.is_empty(), "double_check violation");
+            }
+            post(result) {
+                assert!(result.is_ok(), "post-condition violation");
+            }
+        }
+    }
+
+    assert_that!({ let _ = question_mark("nope"); }, panics);
+    assert!(question_mark("42") == Ok(42));
+
+    struct Counter {
+        count: u32,
+        max: u32,
+    }
+
+    impl Counter {
+        contract! {
+            double_check {
+                assert!(self.count <= self.max, "counter max has been exceeded");
+            }
+
+            fn bump(&mut self, amount: u32) {
+                body {
+                    self.count += amount;
+                    if amount > 1 {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut counter = Counter { count: 0, max: 3 };
+    counter.bump(1);
+    assert_that!(Counter { count: 0, max: 3 }.bump(5), panics);
+}
+
+#[test]
+#[cfg_attr(feature = "disable-post", ignore = "asserts that checks panic")]
+fn old_snapshots() {
+    struct Counter {
+        count: u32,
+    }
+
+    impl Counter {
+        contract! {
+            fn increment(&mut self, amount: u32) {
+                old {
+                    let previous = self.count;
+                    let previous_amount = amount;
+                }
+                body {
+                    self.count += amount;
+                }
+                post {
+                    assert!(self.count == previous + previous_amount, "post-condition violation");
+                }
+            }
+
+            fn broken_increment(&mut self) {
+                old {
+                    let previous = self.count;
+                }
+                body {
+                    self.count += 2;
+                }
+                post {
+                    assert!(self.count == previous + 1, "post-condition violation");
+                }
+            }
+        }
+    }
+
+    let mut counter = Counter { count: 0 };
+    counter.increment(3);
+    assert!(counter.count == 3);
+    assert_that!(Counter { count: 0 }.broken_increment(), panics);
+
+    contract! {
+        fn push_one(v: Vec<u32>) -> Vec<u32> {
+            old {
+                let previous = v.clone();
+            }
+            body {
+                let mut v = v;
+                v.push(1);
+                v
+            }
+            post(pushed) {
+                assert!(pushed.len() == previous.len() + 1, "post-condition violation");
+                assert!(pushed[..previous.len()] == previous[..], "post-condition violation");
+            }
+        }
+    }
+
+    assert!(push_one(vec![2, 3]) == vec![2, 3, 1]);
+}
+
+#[test]
+fn enforcement_features() {
+    use std::cell::Cell;
+
+    thread_local! {
+        static CHECKS_RUN: Cell<u32> = const { Cell::new(0) };
+    }
+
+    fn record() {
+        CHECKS_RUN.with(|c| c.set(c.get() + 1));
+    }
+
+    contract! {
+        double_check {
+            record();
+        }
+
+        fn everything() {
+            pre {
+                record();
+            }
+            post {
+                record();
+            }
+            double_check {
+                record();
+            }
+        }
+    }
+
+    everything();
+
+    let build_enabled = !cfg!(feature = "debug-only") || cfg!(debug_assertions);
+    let mut expected = 0;
+    if build_enabled && !cfg!(feature = "disable-pre") {
+        expected += 1;
+    }
+    if build_enabled && !cfg!(feature = "disable-post") {
+        expected += 1;
+    }
+    if build_enabled && !cfg!(feature = "disable-double-check") {
+        expected += 4;
+    }
+    assert!(CHECKS_RUN.with(Cell::get) == expected);
+}
+
+#[test]
+#[cfg_attr(
+    any(feature = "disable-pre", feature = "disable-post", feature = "disable-double-check"),
+    ignore = "asserts that checks panic"
+)]
+fn violation_reports() {
+    use adhesion::ViolationKind;
+
+    contract! {
+        double_check {
+            true, "never fails"
+        }
+
+        fn halve_small(x: u32) -> u32 {
+            pre {
+                x < 100, "{} is too big", x
+            }
+            body {
+                x / 2
+            }
+            post(half) {
+                half < 10
+            }
+            double_check {
+                // Blocks with statements still run as-is.
+                assert!(x != 40);
+            }
+        }
+
+        fn in_range(x: u32) {
+            pre {
+                // So do ones without a top-level `;`.
+                if x > 50 { panic!("{} is too big", x) }
+                if x == 0 { panic!("zero is too small") }
+            }
+        }
+    }
+
+    assert!(halve_small(4) == 2);
+
+    let violation = violation_of(|| halve_small(300)).unwrap();
+    assert!(violation.kind == ViolationKind::Pre);
+    assert!(violation.fn_name == "halve_small");
+    assert!(violation.condition_text == "x < 100");
+    assert!(violation.file.ends_with("lib.rs"));
+    assert!(violation.message.as_deref() == Some("300 is too big"));
+
+    let violation = violation_of(|| halve_small(20)).unwrap();
+    assert!(violation.kind == ViolationKind::Post);
+    assert!(violation.condition_text == "half < 10");
+    assert!(violation.message.is_none());
+
+    assert!(violation_of(|| halve_small(40)).is_none());
+
+    in_range(5);
+    assert_that!(in_range(0), panics);
+    assert_that!(in_range(51), panics);
+}
+
+#[test]
+#[cfg_attr(feature = "disable-post", ignore = "asserts that checks panic")]
+fn trait_contracts() {
+    contract_trait! {
+        trait Shape<T>: ::std::fmt::Debug where T: Copy {
+            const SIDES: u32;
+
+            fn area(&self, scale: T) -> f64 {
+                pre {
+                    Self::SIDES > 0
+                }
+                body(area_unchecked)
+                post(area) {
+                    area >= 0.0, "{:?} has a negative area", self
+                }
+            }
+
+            fn describe(&self, mut times: u32) -> String {
+                body(describe_unchecked) {
+                    let mut description = String::new();
+                    while times > 0 {
+                        description.push_str("shape");
+                        times -= 1;
+                    }
+                    description
+                }
+                post(description) {
+                    description.len() == 5 * times as usize
+                }
+            }
+
+            fn sides(&self) -> u32 {
+                body {
+                    Self::SIDES
+                }
+            }
+
+            async fn perimeter(&self) -> f64 {
+                body(perimeter_unchecked)
+                post(perimeter) {
+                    perimeter > 0.0
+                }
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct Square(f64);
+
+    impl Shape<u8> for Square {
+        const SIDES: u32 = 4;
+
+        fn area_unchecked(&self, scale: u8) -> f64 {
+            self.0 * self.0 * f64::from(scale)
+        }
+
+        async fn perimeter_unchecked(&self) -> f64 {
+            4.0 * self.0
+        }
+    }
+
+    #[derive(Debug)]
+    struct Broken;
+
+    impl Shape<u8> for Broken {
+        const SIDES: u32 = 3;
+
+        fn area_unchecked(&self, _scale: u8) -> f64 {
+            -1.0
+        }
+
+        fn describe_unchecked(&self, _times: u32) -> String {
+            "broken".to_owned()
+        }
+
+        async fn perimeter_unchecked(&self) -> f64 {
+            0.0
+        }
+    }
+
+    assert!(Square(2.0).area(2) == 8.0);
+    assert!(Square(2.0).describe(2) == "shapeshape");
+    assert!(Square(2.0).sides() == 4);
+    assert!(ready(Square(2.0).perimeter()) == 8.0);
+    assert_that!(Broken.area(1), panics);
+    assert_that!(Broken.describe(1), panics);
+    assert_that!(ready(Broken.perimeter()), panics);
+}
+
+#[test]
+#[cfg_attr(
+    any(feature = "disable-post", feature = "disable-double-check"),
+    ignore = "asserts that checks panic"
+)]
+fn impl_contracts() {
+    use adhesion::{pre, ViolationKind};
+
+    #[derive(Debug)]
+    struct Range {
+        start: u32,
+        end: u32,
+    }
+
+    contract_impl! {
+        impl Range {
+            invariant {
+                self.start <= self.end, "{:?} is backwards", self
+            }
+
+            pub fn new(start: u32, end: u32) -> Range {
+                if start > end {
+                    return Range { start, end };
+                }
+                Self { start, end }
+            }
+
+            #[no_invariant]
+            pub fn backwards() -> Self {
+                Range { start: 1, end: 0 }
+            }
+
+            pub fn len(&self) -> u32 {
+                self.end - self.start
+            }
+
+            #[pre(by <= 10)]
+            pub fn shift_start(&mut self, by: u32) {
+                self.start += by;
+            }
+
+            pub(crate) fn into_end(self) -> u32 {
+                self.end
+            }
+
+            fn swap(&mut self) {
+                ::std::mem::swap(&mut self.start, &mut self.end);
+            }
+
+            pub fn swap_twice(&mut self) {
+                self.swap();
+                self.swap();
+            }
+
+            pub async fn stretch(&mut self, by: u32) {
+                self.end += by;
+            }
+
+            pub const fn start(&self) -> u32 {
+                self.start
+            }
+        }
+    }
+
+    impl Default for Range {
+        fn default() -> Self {
+            Range { start: 3, end: 3 }
+        }
+    }
+
+    contract_impl! {
+        impl PartialEq for Range {
+            invariant {
+                self.start <= self.end
+            }
+
+            fn eq(&self, other: &Self) -> bool {
+                self.start == other.start && self.end == other.end
+            }
+        }
+    }
+
+    let mut range = Range::new(1, 5);
+    assert!(range.len() == 4);
+    range.swap_twice();
+    range.shift_start(4);
+    assert!(range == Range::new(5, 5));
+    assert!(Range::backwards().into_end() == 0);
+    assert!(Range::default().len() == 0);
+
+    let violation = violation_of(|| Range::new(2, 1)).unwrap();
+    assert!(violation.kind == ViolationKind::GlobalDoubleCheck);
+    assert!(violation.fn_name == "new");
+    assert!(violation.message.as_deref() == Some("Range { start: 2, end: 1 } is backwards"));
+
+    let violation = violation_of(|| Range::backwards().len()).unwrap();
+    assert!(violation.kind == ViolationKind::GlobalDoubleCheck);
+    assert!(violation.fn_name == "len");
+
+    assert_that!({ Range::new(1, 5).shift_start(5); }, panics);
+    assert_that!({ Range::new(1, 5).shift_start(11); }, panics);
+    assert_that!({ let _ = Range::backwards() == Range::default(); }, panics);
+
+    let mut range = Range::new(1, 5);
+    ready(range.stretch(1));
+    assert!(range.len() == 5);
+    assert_that!(ready(Range::backwards().stretch(1)), panics);
+    // `const fn`s are left alone.
+    assert!(Range::backwards().start() == 1);
+}
+
+#[test]
+#[cfg_attr(
+    any(feature = "disable-pre", feature = "disable-post"),
+    ignore = "asserts that checks panic"
+)]
+fn fn_qualifiers() {
+    use std::cell::RefCell;
+
+    thread_local! {
+        static LOG: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn log(event: &'static str) {
+        LOG.with(|log| log.borrow_mut().push(event));
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            log("pending");
+        }
+    }
+
+    contract! {
+        unsafe fn read(ptr: *const u32) -> u32 {
+            pre {
+                !ptr.is_null()
+            }
+            body {
+                *ptr
+            }
+        }
+
+        pub extern "C" fn ffi_double(x: u32) -> u32 {
+            body {
+                x * 2
+            }
+            post(doubled) {
+                doubled % 2 == 0
+            }
+        }
+
+        unsafe extern "C" fn ffi_read(ptr: *const u32) -> u32 {
+            body {
+                *ptr
+            }
+        }
+
+        // Leaving out the ABI is deprecated, but still has to be accepted.
+        #[allow(missing_abi)]
+        extern fn ffi_identity(x: u32) -> u32 {
+            body {
+                x
+            }
+        }
+
+        pub const fn halve(x: u32) -> u32 {
+            pre {
+                x & 1 == 0, "only even numbers can be halved"
+            }
+            body {
+                x / 2
+            }
+            post(half) {
+                half * 2 == x
+            }
+        }
+
+        pub(crate) const unsafe fn offset_by_one(x: u32) -> u32 {
+            pre {
+                assert!(x < u32::MAX);
+            }
+            body {
+                x + 1
+            }
+        }
+
+        async fn fetch(ready: bool) -> Result<u32, &'static str> {
+            pre {
+                log("pre");
+            }
+            body {
+                if !ready {
+                    return Err("not ready");
+                }
+                YieldOnce(false).await;
+                log("body");
+                Ok(5)
+            }
+            post(value) {
+                log("post");
+                assert!(value.is_err() || value == Ok(5));
+            }
+        }
+
+        async fn fetch_or_panic(ready: bool) -> u32 {
+            body {
+                YieldOnce(false).await;
+                assert!(ready, "not ready");
+                5
+            }
+            on_panic {
+                log("on_panic");
+            }
+        }
+    }
+
+    const _: () = assert!(halve(8) == 4);
+    const _: () = assert!(unsafe { offset_by_one(1) } == 2);
+    assert_that!(halve(3), panics);
+
+    let value = 7;
+    assert!(unsafe { read(&value) } == 7);
+    assert_that!(unsafe { read(::std::ptr::null()) }, panics);
+    assert!(unsafe { ffi_read(&value) } == 7);
+    assert!(ffi_double(3) == 6);
+    assert!(ffi_identity(3) == 3);
+
+    let future = fetch(true);
+    log("created");
+    assert!(block_on(future) == Ok(5));
+    assert!(block_on(fetch(false)) == Err("not ready"));
+    LOG.with(|log| {
+        assert!(*log.borrow() == ["created", "pre", "pending", "body", "post", "pre", "post"]);
+    });
+
+    assert!(block_on(fetch_or_panic(true)) == 5);
+    assert_that!(block_on(fetch_or_panic(false)), panics);
+    LOG.with(|log| assert!(log.borrow().last() == Some(&"on_panic")));
+}
+
+#[test]
+#[cfg_attr(feature = "disable-post", ignore = "asserts that checks panic")]
+fn post_patterns() {
+    contract! {
+        fn div_rem(dividend: u32, divisor: u32) -> (u32, u32) {
+            pre {
+                divisor != 0
+            }
+            body {
+                (dividend / divisor, dividend % divisor)
+            }
+            post((quotient, remainder)) {
+                quotient * divisor + remainder == dividend && remainder < divisor
+            }
+        }
+
+        fn parse_small(text: &str) -> Result<u8, String> {
+            body {
+                text.parse().map_err(|_| format!("{:?} is not a number", text))
+            }
+            post(Ok(value)) {
+                value < 100, "{} is not small", value
+            }
+        }
+
+        fn shout(text: &str) -> Result<String, String> {
+            body {
+                if text.is_empty() {
+                    Err("nothing to shout".to_owned())
+                } else {
+                    Ok(text.to_uppercase())
+                }
+            }
+            post(Ok(ref shouted)) {
+                shouted.len() == text.len()
+            }
+        }
+
+        fn largest(items: &mut [u32]) -> &mut u32 {
+            pre {
+                !items.is_empty()
+            }
+            old {
+                let max = items.iter().copied().max();
+            }
+            body {
+                items.iter_mut().max().unwrap()
+            }
+            post(ref largest) {
+                Some(**largest) == max
+            }
+        }
+    }
+
+    assert!(div_rem(17, 5) == (3, 2));
+    assert_that!(div_rem(17, 0), panics);
+
+    assert!(parse_small("42") == Ok(42));
+    // The pattern doesn't match an `Err`, so the check doesn't run.
+    assert!(parse_small("forty-two").is_err());
+    assert_that!(drop(parse_small("200")), panics);
+
+    assert!(shout("hey") == Ok("HEY".to_owned()));
+    assert!(shout("").is_err());
+
+    let mut items = [3, 9, 4];
+    *largest(&mut items) = 1;
+    assert!(items == [3, 1, 4]);
+}
+
+#[test]
+#[cfg_attr(feature = "disable-post", ignore = "asserts that checks panic")]
+fn post_variants() {
+    contract! {
+        fn checked_root(value: i64) -> Result<i64, String> {
+            body {
+                if value < 0 {
+                    Err(format!("{} is negative", value))
+                } else {
+                    Ok((value as f64).sqrt() as i64)
+                }
+            }
+            post(ret) {
+                ret.is_ok() == (value >= 0)
+            }
+            post_ok(root) {
+                root * root <= value, "{} is too big", root
+            }
+            post_err(message) {
+                message.contains("negative")
+            }
+        }
+
+        fn find_even(values: &[u32]) -> Option<usize> {
+            body {
+                values.iter().position(|value| value % 2 == 1)
+            }
+            post_some(index) {
+                values[*index] & 1 == 0
+            }
+            post_none {
+                values.iter().all(|value| value % 2 == 1)
+            }
+        }
+    }
+
+    assert!(checked_root(17) == Ok(4));
+    assert!(checked_root(-1).is_err());
+
+    // The body looks for odd values instead, which either block catches.
+    assert!(find_even(&[]).is_none());
+    assert_that!(find_even(&[2, 3]), panics);
+    assert_that!(find_even(&[2, 4]), panics);
+}
+
+#[test]
+fn fallible_pre() {
+    use adhesion::ViolationKind;
+
+    #[derive(Debug, PartialEq)]
+    enum RootError {
+        Negative,
+        TooBig(i64),
+        Contract(&'static str),
+    }
+
+    impl From<ContractViolation> for RootError {
+        fn from(violation: ContractViolation) -> Self {
+            assert!(violation.kind == ViolationKind::Pre);
+            RootError::Contract(violation.condition_text)
+        }
+    }
+
+    contract! {
+        fn checked_root(value: i64) -> Result<i64, RootError> {
+            pre_err {
+                value >= 0 => RootError::Negative,
+                value < 1_000_000 => RootError::TooBig(value),
+                value != 42,
+            }
+            body {
+                Ok((value as f64).sqrt() as i64)
+            }
+        }
+
+        fn boxed_error(text: &str) -> Result<usize, Box<dyn std::error::Error>> {
+            pre_err {
+                !text.is_empty() => "nothing to parse",
+            }
+            body {
+                Ok(text.parse()?)
+            }
+        }
+    }
+
+    assert!(checked_root(16) == Ok(4));
+    assert!(checked_root(-4) == Err(RootError::Negative));
+    assert!(checked_root(1_000_000) == Err(RootError::TooBig(1_000_000)));
+    assert!(checked_root(42) == Err(RootError::Contract("value != 42")));
+
+    assert!(boxed_error("12").unwrap() == 12);
+    assert!(boxed_error("").unwrap_err().to_string() == "nothing to parse");
+    assert!(boxed_error("twelve").is_err());
+}
+
+#[test]
+#[cfg_attr(feature = "disable-double-check", ignore = "asserts that checks panic")]
+fn panic_checks() {
+    use adhesion::ViolationKind;
+    use std::cell::Cell;
+    use std::panic::AssertUnwindSafe;
+
+    struct Buffer {
+        items: Vec<u32>,
+        len: usize,
+    }
+
+    impl Buffer {
+        contract! {
+            double_check(unwind) {
+                self.items.len() == self.len, "len is out of sync"
+            }
+
+            // Not exception safe: `len` is updated before a push that may
+            // panic.
+            fn push_checked(&mut self, item: u32) {
+                body {
+                    self.len += 1;
+                    assert!(item != 0, "zero is not allowed");
+                    self.items.push(item);
+                }
+            }
+
+            fn push_safely(&mut self, item: u32) {
+                body {
+                    assert!(item != 0, "zero is not allowed");
+                    self.items.push(item);
+                    self.len += 1;
+                }
+            }
+        }
+    }
+
+    let mut buffer = Buffer { items: Vec::new(), len: 0 };
+    buffer.push_checked(1);
+    let violation = violation_of(AssertUnwindSafe(|| buffer.push_checked(0))).unwrap();
+    assert!(violation.kind == ViolationKind::GlobalDoubleCheck);
+
+    let mut buffer = Buffer { items: Vec::new(), len: 0 };
+    // The original panic carries on when every check passes.
+    assert!(violation_of(AssertUnwindSafe(|| buffer.push_safely(0))).is_none());
+    assert!(buffer.len == 0);
+
+    let cleaned_up = Cell::new(false);
+    contract! {
+        fn fallible(fail: bool, cleaned_up: &Cell<bool>) -> u32 {
+            body {
+                if fail {
+                    panic!("failed");
+                }
+                1
+            }
+            on_panic {
+                cleaned_up.set(true);
+            }
+            double_check(unwind) {
+                !cleaned_up.get() || fail
+            }
+        }
+
+        fn left_dirty(cleaned_up: &Cell<bool>) {
+            body {
+                panic!("failed")
+            }
+            on_panic {
+                cleaned_up.get(), "nothing was cleaned up"
+            }
+        }
+    }
+
+    assert!(fallible(false, &cleaned_up) == 1);
+    assert!(!cleaned_up.get());
+    assert!(violation_of(AssertUnwindSafe(|| fallible(true, &cleaned_up))).is_none());
+    assert!(cleaned_up.get());
+
+    let violation = violation_of(|| left_dirty(&Cell::new(false))).unwrap();
+    assert!(violation.kind == ViolationKind::OnPanic);
+}
+
+#[test]
+#[cfg_attr(feature = "disable-double-check", ignore = "asserts that checks panic")]
+fn outermost_double_check() {
+    use adhesion::ViolationKind;
+    use std::panic::AssertUnwindSafe;
+
+    struct Range {
+        low: u32,
+        high: u32,
+    }
+
+    impl Range {
+        contract! {
+            double_check(outermost) {
+                self.low <= self.high
+            }
+
+            fn set_low(&mut self, low: u32) {
+                body {
+                    self.low = low;
+                }
+            }
+
+            fn set_high(&mut self, high: u32) {
+                body {
+                    self.high = high;
+                }
+            }
+
+            // The invariant is broken in between the inner calls, which is
+            // fine as long as it holds again by the time this returns.
+            fn shift_up(&mut self, by: u32) {
+                body {
+                    self.set_low(self.low + by);
+                    self.set_high(self.high + by);
+                }
+            }
+
+            fn copy_into(&self, other: &mut Range) {
+                body {
+                    other.set_low(self.low);
+                    other.set_high(self.high);
+                }
+            }
+
+            async fn raise_high(&mut self, by: u32) {
+                body {
+                    YieldOnce(false).await;
+                    self.high += by;
+                }
+            }
+        }
+    }
+
+    let mut range = Range { low: 1, high: 2 };
+    range.shift_up(5);
+    assert!(range.low == 6 && range.high == 7);
+    assert_that!(Range { low: 1, high: 2 }.set_low(3), panics);
+
+    // Calls into another object are still checked on their own.
+    assert_that!(range.copy_into(&mut Range { low: 0, high: 3 }), panics);
+
+    // An `async fn` may be finished on another thread than the one it started
+    // on, so it checks on every call and doesn't make later calls nested.
+    finish_on_another_thread(range.raise_high(1));
+    assert!(range.high == 8);
+    let violation = violation_of(AssertUnwindSafe(|| range.set_low(9))).unwrap();
+    assert!(violation.kind == ViolationKind::GlobalDoubleCheck);
+
+    // So are calls into a field, even though it has the same address.
+    struct Wrapper {
+        range: Range,
+    }
+
+    impl Wrapper {
+        contract! {
+            double_check(outermost) {}
+
+            fn lower(&mut self, low: u32) {
+                body {
+                    self.range.set_low(low);
+                }
+            }
+        }
+    }
+
+    let mut wrapper = Wrapper { range: Range { low: 1, high: 2 } };
+    wrapper.lower(0);
+    assert_that!(Wrapper { range: Range { low: 1, high: 2 } }.lower(3), panics);
+
+    // `fn`s without a receiver are only nested in calls to themselves.
+    contract! {
+        double_check(outermost) {
+            x < 10
+        }
+
+        fn outer(x: u32) {
+            body {
+                inner(x + 20, 0);
+            }
+        }
+    }
+
+    contract! {
+        double_check(outermost) {
+            x < 20
+        }
+
+        fn inner(x: u32, depth: u32) {
+            body {
+                if depth > 0 {
+                    inner(x + 10, depth - 1);
+                }
+            }
+        }
+    }
+
+    inner(15, 1);
+    assert_that!(outer(5), panics);
+}
+
+#[test]
+fn reentrancy_guard() {
+    use adhesion::ViolationKind;
+    use std::panic::AssertUnwindSafe;
+
+    struct Counter {
+        count: u32,
+    }
+
+    impl Counter {
+        contract! {
+            reentrancy_guard;
+
+            fn count(&self) -> u32 {
+                body {
+                    self.count
+                }
+            }
+
+            fn bump_with(&mut self, callback: impl FnOnce(&mut Counter)) {
+                body {
+                    self.count += 1;
+                    callback(self);
+                }
+            }
+
+            async fn bump_later(&mut self) {
+                body {
+                    YieldOnce(false).await;
+                    self.count += 1;
+                }
+            }
+
+            // Without a receiver, there's no object to re-enter.
+            fn new() -> Counter {
+                body {
+                    Counter { count: 0 }
+                }
+            }
+        }
+    }
+
+    let mut counter = Counter::new();
+    counter.bump_with(|_| {});
+    counter.bump_with(|_| {});
+    assert!(counter.count() == 2);
+
+    // Calls into another object from a callback are fine.
+    let mut other = Counter::new();
+    counter.bump_with(|_| other.bump_with(|_| {}));
+    assert!(other.count() == 1);
+
+    let violation = violation_of(AssertUnwindSafe(|| {
+        counter.bump_with(|counter| counter.bump_with(|_| {}));
+    }))
+    .unwrap();
+    assert!(violation.kind == ViolationKind::Reentrancy);
+    assert!(violation.fn_name == "bump_with");
+
+    // Leaving the object by unwinding doesn't keep it marked as entered.
+    assert!(counter.count() == 4);
+
+    // An `async fn` may be finished on another thread than the one it started
+    // on, so it isn't guarded and doesn't leave the object marked as entered.
+    finish_on_another_thread(counter.bump_later());
+    assert!(counter.count() == 5);
+
+    // Neither calls into a field at the same address nor calls through other
+    // invocations count as re-entries.
+    struct Tally {
+        counter: Counter,
+    }
+
+    impl Tally {
+        contract! {
+            reentrancy_guard;
+
+            fn count(&self) -> u32 {
+                body {
+                    self.counter.count()
+                }
+            }
+        }
+
+        contract! {
+            double_check(outermost) {}
+
+            fn bump(&mut self) {
+                body {
+                    self.counter.bump_with(|_| {});
+                    self.count();
+                }
+            }
+        }
+    }
+
+    let mut tally = Tally { counter: Counter::new() };
+    tally.bump();
+    assert!(tally.count() == 1);
+
+    // Invocations that a macro expands to share a source location, but are
+    // still told apart.
+    struct Pair {
+        calls: u32,
+    }
+
+    macro_rules! guarded_pair {
+        ($outer: ident, $inner: ident) => {
+            impl Pair {
+                contract! {
+                    reentrancy_guard;
+
+                    fn $outer(&mut self) {
+                        body {
+                            self.$inner();
+                        }
+                    }
+                }
+
+                contract! {
+                    reentrancy_guard;
+
+                    fn $inner(&mut self) {
+                        body {
+                            self.calls += 1;
+                        }
+                    }
+                }
+            }
+        };
+    }
+
+    guarded_pair!(outer, inner);
+
+    let mut pair = Pair { calls: 0 };
+    pair.outer();
+    assert!(pair.calls == 1);
+}
+
+#[test]
+#[cfg_attr(
+    any(feature = "disable-pre", feature = "disable-double-check"),
+    ignore = "asserts that checks panic"
+)]
+fn contracted_closures() {
+    use adhesion::ViolationKind;
+    use std::panic::AssertUnwindSafe;
+
+    let max = 10;
+    let double = contract_closure!(|x: u32| -> u32 {
+        pre {
+            x <= max, "{} is too big", x
+        }
+        body {
+            x * 2
+        }
+        post(doubled) {
+            doubled <= max * 2
+        }
+    });
+    assert!(double(5) == 10);
+    assert_that!(
+        {
+            double(11);
+        },
+        panics
+    );
+
+    let violation = violation_of(|| double(11)).unwrap();
+    assert!(violation.kind == ViolationKind::Pre);
+    assert!(violation.fn_name == "closure");
+
+    // Captured variables can be changed by `body` and compared in `post`.
+    let mut total = 0;
+    let mut add = contract_closure!(move |x| {
+        old {
+            let previous = total;
+        }
+        body {
+            total += x;
+            total
+        }
+        post(sum) {
+            sum == previous + x
+        }
+        double_check {
+            total < 100
+        }
+    });
+    assert!(add(40) == 40);
+    assert!(add(50) == 90);
+    assert!(catch_unwind(AssertUnwindSafe(|| add(20))).is_err());
+    assert!(total == 0);
+
+    // Closures can be passed wherever an ordinary one can.
+    let evens: Vec<u32> = (1..4)
+        .map(contract_closure!(|x| {
+            body {
+                x * 2
+            }
+            post(even) {
+                even & 1 == 0
+            }
+        }))
+        .collect();
+    assert!(evens == [2, 4, 6]);
+}
+
+#[test]
+#[cfg_attr(
+    any(feature = "disable-pre", feature = "disable-post"),
+    ignore = "asserts that checks panic"
+)]
+fn callback_contracts() {
+    use adhesion::{Blame, ViolationKind};
+
+    contract! {
+        fn fold(items: &[u32], limit: u32, f: impl Fn(u32, u32) -> u32) -> u32 {
+            pre {
+                !items.is_empty()
+            }
+            pre {
+                f: fn_contract!(|total, item| pre { item <= limit, "{} is past the limit", item } post(next) { next >= total })
+            }
+            body {
+                items.iter().fold(0, |total, &item| f(total, item))
+            }
+        }
+
+        fn call_twice(f: impl Fn() -> u32) -> u32 {
+            pre {
+                f: fn_contract!(|| post(n) { n > 0 })
+            }
+            body {
+                f() + f()
+            }
+        }
+    }
+
+    assert!(fold(&[1, 2, 3], 3, |total, item| total + item) == 6);
+    assert!(call_twice(|| 2) == 4);
+
+    let violation = violation_of(|| fold(&[1, 5], 3, |total, item| total + item)).unwrap();
+    assert!(violation.kind == ViolationKind::CallbackPre);
+    assert!(violation.kind.blame() == Blame::Callee);
+    assert!(violation.fn_name == "fold");
+    assert!(violation.message.as_deref() == Some("5 is past the limit"));
+
+    let violation = violation_of(|| fold(&[3, 2], 3, |_, item| item)).unwrap();
+    assert!(violation.kind == ViolationKind::CallbackPost);
+    assert!(violation.kind.blame() == Blame::Caller);
+
+    // An ordinary `pre` block still blames the caller.
+    let violation = violation_of(|| fold(&[], 3, |total, item| total + item)).unwrap();
+    assert!(violation.kind == ViolationKind::Pre);
+    assert!(violation.kind.blame() == Blame::Caller);
+
+    assert!(violation_of(|| call_twice(|| 0)).unwrap().kind == ViolationKind::CallbackPost);
+}
+
+#[test]
+fn loop_contracts() {
+    use adhesion::ViolationKind;
+
+    fn sum_to(n: u32) -> u32 {
+        let mut sum = 0;
+        contract_loop! {
+            invariant {
+                sum <= n * n, "sum {} is too big", sum
+            }
+            for i in 0..=n {
+                sum += i;
+            }
+        }
+        sum
+    }
+
+    fn first_square_above(limit: u32) -> u32 {
+        let mut i = 0;
+        contract_loop! {
+            decreases {
+                (limit + 1).saturating_sub(i * i)
+            }
+            'search: loop {
+                i += 1;
+                if i * i > limit {
+                    break 'search i * i;
+                }
+            }
+        }
+    }
+
+    assert!(sum_to(4) == 10);
+    assert!(first_square_above(10) == 16);
+
+    // `continue` still goes through the checks.
+    let mut countdown = 5;
+    let mut odd = 0;
+    contract_loop! {
+        decreases {
+            countdown
+        }
+        invariant {
+            odd <= 5 - countdown
+        }
+        while countdown > 0 {
+            countdown -= 1;
+            if countdown & 1 == 0 {
+                continue;
+            }
+            odd += 1;
+        }
+    }
+    assert!(odd == 2);
+
+    let violation = violation_of(|| {
+        let mut items = vec![1, 2, 3];
+        contract_loop! {
+            invariant {
+                items.len() < 5, "{} items is too many", items.len()
+            }
+            while let Some(item) = items.pop() {
+                items.push(item);
+                items.push(item);
+            }
+        }
+    })
+    .unwrap();
+    assert!(violation.kind == ViolationKind::LoopInvariant);
+    assert!(violation.fn_name == "while");
+    assert!(violation.iteration == Some(4));
+    assert!(violation.message.as_deref() == Some("5 items is too many"));
+
+    let violation = violation_of(|| {
+        let mut x = 10_i32;
+        contract_loop! {
+            decreases {
+                x
+            }
+            loop {
+                x -= 3;
+            }
+        }
+    })
+    .unwrap();
+    assert!(violation.kind == ViolationKind::LoopVariant);
+    assert!(violation.iteration == Some(5));
+    assert!(violation.to_string().contains("on iteration 5"));
+
+    let violation = violation_of(|| {
+        let mut x = 0;
+        contract_loop! {
+            decreases {
+                x
+            }
+            while x < 3 {
+                x += 1;
+            }
+        }
+    })
+    .unwrap();
+    assert!(violation.kind == ViolationKind::LoopVariant);
+    assert!(violation.iteration == Some(2));
+    assert!(violation.message.as_deref() == Some("didn't decrease since the previous iteration"));
+}
+
+#[test]
+fn recursion_measures() {
+    use adhesion::ViolationKind;
+
+    enum Tree {
+        Leaf,
+        Node(Box<Tree>, Box<Tree>),
+    }
+
+    impl Tree {
+        fn height(&self) -> usize {
+            match *self {
+                Tree::Leaf => 0,
+                Tree::Node(ref left, ref right) => 1 + left.height().max(right.height()),
+            }
+        }
+    }
+
+    contract! {
+        fn count_leaves(tree: &Tree) -> usize {
+            decreases {
+                tree.height()
+            }
+            body {
+                match *tree {
+                    Tree::Leaf => 1,
+                    Tree::Node(ref left, ref right) => count_leaves(left) + count_leaves(right),
+                }
+            }
+        }
+
+        // Forgets to move on to a smaller `n` when it's odd.
+        fn collatz_steps(n: u64, depth: u32) -> u32 {
+            decreases {
+                100 - depth
+            }
+            body {
+                if n <= 1 {
+                    0
+                } else if n & 1 == 0 {
+                    1 + collatz_steps(n / 2, depth + 1)
+                } else {
+                    1 + collatz_steps(3 * n + 1, depth)
+                }
+            }
+        }
+
+        fn halve(n: i32) -> i32 {
+            decreases {
+                n
+            }
+            body {
+                if n == 0 {
+                    0
+                } else {
+                    halve(n / 2)
+                }
+            }
+        }
+    }
+
+    let leaf = || Box::new(Tree::Leaf);
+    let tree = Tree::Node(Box::new(Tree::Node(leaf(), leaf())), leaf());
+    assert!(count_leaves(&tree) == 3);
+    // Separate calls don't count as recursion.
+    assert!(count_leaves(&Tree::Leaf) == 1);
+    assert!(collatz_steps(8, 0) == 3);
+    assert!(halve(100) == 0);
+
+    let violation = violation_of(|| collatz_steps(3, 0)).unwrap();
+    assert!(violation.kind == ViolationKind::Decreases);
+    assert!(violation.fn_name == "collatz_steps");
+    assert!(violation.message.as_deref() == Some("didn't decrease since the call it recursed from"));
+
+    let violation = violation_of(|| halve(-4)).unwrap();
+    assert!(violation.message.as_deref() == Some("went below zero"));
+
+    // The measures of a call that panicked are gone, too.
+    assert!(halve(3) == 0);
+}
+
+#[test]
+#[cfg_attr(feature = "disable-double-check", ignore = "asserts that checks panic")]
+fn derived_invariants() {
+    use adhesion::{Invariant, ViolationKind};
+
+    #[derive(Invariant)]
+    #[invariant(self.count <= self.max, "count {} is past {}", self.count, self.max)]
+    struct Counter {
+        count: u32,
+        max: u32,
+    }
+
+    #[derive(Invariant)]
+    #[invariant(!self.0.is_empty())]
+    struct Named<T: Invariant>(String, T);
+
+    #[derive(Invariant)]
+    enum Tree {
+        Leaf(Counter),
+        Node {
+            children: Vec<Tree>,
+            #[allow(dead_code)]
+            depth: u8,
+        },
+    }
+
+    // Fields without an invariant are left alone.
+    assert!(Counter { count: 0, max: 0 }.check_invariant().is_ok());
+
+    let tree = Tree::Node {
+        children: vec![
+            Tree::Leaf(Counter { count: 1, max: 2 }),
+            Tree::Node {
+                children: vec![Tree::Leaf(Counter { count: 3, max: 2 })],
+                depth: 1,
+            },
+        ],
+        depth: 0,
+    };
+    let violation = tree.check_invariant().unwrap_err();
+    assert!(violation.kind == ViolationKind::Invariant);
+    assert!(violation.fn_name == "Counter");
+    assert!(violation.condition_text == "self.count <= self.max");
+    assert!(violation.message.as_deref() == Some("count 3 is past 2"));
+
+    let named = Named(String::new(), Counter { count: 3, max: 2 });
+    assert!(named.check_invariant().unwrap_err().fn_name == "Named");
+    let named = Named(String::from("a"), Some(Box::new(Counter { count: 3, max: 2 })));
+    assert!(named.check_invariant().unwrap_err().fn_name == "Counter");
+
+    impl Counter {
+        contract! {
+            double_check(invariant);
+
+            fn tick_up(&mut self) {
+                body {
+                    self.count += 1;
+                }
+            }
+
+            fn new(max: u32) -> Counter {
+                body {
+                    Counter { count: 0, max }
+                }
+            }
+        }
+    }
+
+    let mut counter = Counter::new(1);
+    counter.tick_up();
+    let violation = violation_of(move || counter.tick_up()).unwrap();
+    assert!(violation.kind == ViolationKind::Invariant);
+    assert!(violation.message.as_deref() == Some("count 2 is past 1"));
+}
@@ -0,0 +1,179 @@
+// `contract!` used to need `#![recursion_limit]` raised for every few `fn`s
+// in it, so this checks that a large invocation compiles with the default.
+#[macro_use]
+extern crate adhesion;
+#[macro_use]
+extern crate galvanic_assert;
+
+use adhesion::pre;
+
+#[derive(Default)]
+struct Accumulator {
+    total: u64,
+}
+
+// Emits a single `contract!` with one method per name.
+macro_rules! accumulator_fns {
+    ($($name: ident)*) => {
+        #[allow(dead_code)]
+        impl Accumulator {
+            contract! {
+                double_check {
+                    self.total < 1_000_000
+                }
+
+                $(
+                    fn $name<T>(&mut self, value: T) -> u64 where T: Into<u64> + Copy {
+                        pre { value.into() < 1_000 }
+                        body { self.total += value.into(); self.total }
+                        post(total) { total >= value.into() }
+                    }
+                )*
+            }
+        }
+    };
+}
+
+accumulator_fns! {
+    add_000 add_001 add_002 add_003 add_004 add_005 add_006 add_007 add_008 add_009
+    add_010 add_011 add_012 add_013 add_014 add_015 add_016 add_017 add_018 add_019
+    add_020 add_021 add_022 add_023 add_024 add_025 add_026 add_027 add_028 add_029
+    add_030 add_031 add_032 add_033 add_034 add_035 add_036 add_037 add_038 add_039
+    add_040 add_041 add_042 add_043 add_044 add_045 add_046 add_047 add_048 add_049
+    add_050 add_051 add_052 add_053 add_054 add_055 add_056 add_057 add_058 add_059
+    add_060 add_061 add_062 add_063 add_064 add_065 add_066 add_067 add_068 add_069
+    add_070 add_071 add_072 add_073 add_074 add_075 add_076 add_077 add_078 add_079
+    add_080 add_081 add_082 add_083 add_084 add_085 add_086 add_087 add_088 add_089
+    add_090 add_091 add_092 add_093 add_094 add_095 add_096 add_097 add_098 add_099
+    add_100 add_101 add_102 add_103 add_104 add_105 add_106 add_107 add_108 add_109
+    add_110 add_111 add_112 add_113 add_114 add_115 add_116 add_117 add_118 add_119
+    add_120 add_121 add_122 add_123 add_124 add_125 add_126 add_127 add_128 add_129
+    add_130 add_131 add_132 add_133 add_134 add_135 add_136 add_137 add_138 add_139
+    add_140 add_141 add_142 add_143 add_144 add_145 add_146 add_147 add_148 add_149
+    add_150 add_151 add_152 add_153 add_154 add_155 add_156 add_157 add_158 add_159
+    add_160 add_161 add_162 add_163 add_164 add_165 add_166 add_167 add_168 add_169
+    add_170 add_171 add_172 add_173 add_174 add_175 add_176 add_177 add_178 add_179
+    add_180 add_181 add_182 add_183 add_184 add_185 add_186 add_187 add_188 add_189
+    add_190 add_191 add_192 add_193 add_194 add_195 add_196 add_197 add_198 add_199
+}
+
+// Long conditions used to be scanned one token at a time, too.
+contract! {
+    fn sum_small(values: [u32; 20]) -> u32 {
+        pre {
+            values.len() == 20 &&
+            values[0] < 100 &&
+            values[1] < 100 &&
+            values[2] < 100 &&
+            values[3] < 100 &&
+            values[4] < 100 &&
+            values[5] < 100 &&
+            values[6] < 100 &&
+            values[7] < 100 &&
+            values[8] < 100 &&
+            values[9] < 100 &&
+            values[10] < 100 &&
+            values[11] < 100 &&
+            values[12] < 100 &&
+            values[13] < 100 &&
+            values[14] < 100 &&
+            values[15] < 100 &&
+            values[16] < 100 &&
+            values[17] < 100 &&
+            values[18] < 100 &&
+            values[19] < 100,
+            "{:?} has a value of 100 or more", values
+        }
+        body {
+            values.iter().sum()
+        }
+    }
+}
+
+#[pre(
+    values.len() == 20 &&
+    values[0] < 100 &&
+    values[1] < 100 &&
+    values[2] < 100 &&
+    values[3] < 100 &&
+    values[4] < 100 &&
+    values[5] < 100 &&
+    values[6] < 100 &&
+    values[7] < 100 &&
+    values[8] < 100 &&
+    values[9] < 100 &&
+    values[10] < 100 &&
+    values[11] < 100 &&
+    values[12] < 100 &&
+    values[13] < 100 &&
+    values[14] < 100 &&
+    values[15] < 100 &&
+    values[16] < 100 &&
+    values[17] < 100 &&
+    values[18] < 100 &&
+    values[19] < 100
+)]
+fn sum_small_attribute(values: [u32; 20]) -> u32 {
+    values.iter().sum()
+}
+
+struct Small([u32; 20]);
+
+contract_impl! {
+    impl Small {
+        invariant {
+            self.0.len() == 20 &&
+            self.0[0] < 100 &&
+            self.0[1] < 100 &&
+            self.0[2] < 100 &&
+            self.0[3] < 100 &&
+            self.0[4] < 100 &&
+            self.0[5] < 100 &&
+            self.0[6] < 100 &&
+            self.0[7] < 100 &&
+            self.0[8] < 100 &&
+            self.0[9] < 100 &&
+            self.0[10] < 100 &&
+            self.0[11] < 100 &&
+            self.0[12] < 100 &&
+            self.0[13] < 100 &&
+            self.0[14] < 100 &&
+            self.0[15] < 100 &&
+            self.0[16] < 100 &&
+            self.0[17] < 100 &&
+            self.0[18] < 100 &&
+            self.0[19] < 100
+        }
+
+        pub fn get(&self, index: usize) -> u32 {
+            self.0[index]
+        }
+    }
+}
+
+#[test]
+#[cfg_attr(feature = "disable-pre", ignore = "asserts that checks panic")]
+fn many_fns() {
+    let mut accumulator = Accumulator::default();
+    assert!(accumulator.add_000(1u8) == 1);
+    assert!(accumulator.add_100(2u16) == 3);
+    assert!(accumulator.add_199(3u32) == 6);
+    assert_that!(Accumulator::default().add_042(1_000u32), panics);
+}
+
+#[test]
+#[cfg_attr(
+    any(feature = "disable-pre", feature = "disable-double-check"),
+    ignore = "asserts that checks panic"
+)]
+fn long_conditions() {
+    let mut values = [1; 20];
+    assert!(sum_small(values) == 20);
+    assert!(sum_small_attribute(values) == 20);
+    assert!(Small(values).get(19) == 1);
+
+    values[19] = 100;
+    assert_that!(sum_small(values), panics);
+    assert_that!(sum_small_attribute(values), panics);
+    assert_that!(Small(values).get(0), panics);
+}
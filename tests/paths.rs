@@ -0,0 +1,77 @@
+//! Contracts used through `use` imports alone, without `#[macro_use]`, which
+//! needs every macro that `contract!` uses internally to be invoked through a
+//! path.
+use adhesion::contract;
+
+contract! {
+    double_check {
+        true
+    }
+
+    #[allow(clippy::multiple_bound_locations)]
+    fn clamp<T: PartialOrd>(value: T, low: T, high: T) -> T
+    where
+        T: Copy,
+    {
+        pre {
+            low <= high
+        }
+        body {
+            if value < low {
+                low
+            } else if value > high {
+                high
+            } else {
+                value
+            }
+        }
+        post(clamped) {
+            low <= clamped && clamped <= high
+        }
+    }
+
+    pub const unsafe extern "C" fn identity(x: u32) -> u32 {
+        body {
+            x
+        }
+    }
+}
+
+#[test]
+fn contract_alone() {
+    assert!(clamp(5, 0, 3) == 3);
+    assert!(unsafe { identity(3) } == 3);
+    assert!(std::panic::catch_unwind(|| clamp(1, 3, 0)).is_err());
+}
+
+#[test]
+fn trait_and_impl_contracts() {
+    use adhesion::{contract_impl, contract_trait};
+
+    contract_trait! {
+        trait Named {
+            fn name(&self) -> String {
+                body(name_unchecked)
+                post(name) {
+                    !name.is_empty()
+                }
+            }
+        }
+    }
+
+    struct Thing;
+
+    contract_impl! {
+        impl Named for Thing {
+            invariant {
+                true
+            }
+
+            fn name_unchecked(&self) -> String {
+                "thing".to_owned()
+            }
+        }
+    }
+
+    assert!(Thing.name() == "thing");
+}
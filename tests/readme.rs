@@ -1,2 +0,0 @@
-include!(concat!(env!("OUT_DIR"), "/skeptic-tests.rs"));
-
@@ -0,0 +1,14 @@
+use adhesion::contract;
+
+contract! {
+    async fn countdown(depth: u32) -> u32 {
+        decreases {
+            depth
+        }
+        body {
+            depth
+        }
+    }
+}
+
+fn main() {}
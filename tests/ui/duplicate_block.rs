@@ -0,0 +1,17 @@
+use adhesion::contract;
+
+contract! {
+    fn checked(x: u32) -> u32 {
+        pre {
+            x > 0
+        }
+        pre {
+            x < 10
+        }
+        body {
+            x
+        }
+    }
+}
+
+fn main() {}
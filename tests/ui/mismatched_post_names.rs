@@ -0,0 +1,9 @@
+use adhesion::post;
+
+#[post(a => a > 0)]
+#[post(b => b < 10)]
+fn checked(x: u32) -> u32 {
+    x
+}
+
+fn main() {}
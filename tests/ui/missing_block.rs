@@ -0,0 +1,12 @@
+use adhesion::contract;
+
+contract! {
+    fn checked(x: u32) -> u32 {
+        pre x > 0
+        body {
+            x
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use adhesion::contract;
+
+contract! {
+    struct Checked;
+}
+
+fn main() {}
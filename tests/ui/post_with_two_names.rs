@@ -0,0 +1,14 @@
+use adhesion::contract;
+
+contract! {
+    fn checked(x: u32) -> u32 {
+        body {
+            x
+        }
+        post(a, b) {
+            a > 0
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use adhesion::contract;
+
+contract! {
+    fn checked(x: u32) -> u32 {
+        body {
+            x
+        };
+    }
+}
+
+fn main() {}
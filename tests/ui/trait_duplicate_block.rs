@@ -0,0 +1,17 @@
+use adhesion::contract_trait;
+
+contract_trait! {
+    trait Checked {
+        fn checked(&self, x: u32) -> u32 {
+            pre {
+                x > 0
+            }
+            pre {
+                x < 10
+            }
+            body(checked_unchecked)
+        }
+    }
+}
+
+fn main() {}
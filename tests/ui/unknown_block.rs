@@ -0,0 +1,17 @@
+use adhesion::contract;
+
+contract! {
+    fn checked(x: u32) -> u32 {
+        pre {
+            x > 0
+        }
+        body {
+            x
+        }
+        post_condition {
+            x > 0
+        }
+    }
+}
+
+fn main() {}
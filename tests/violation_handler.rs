@@ -0,0 +1,65 @@
+//! The violation handler is global, so this gets its own test binary to keep
+//! it from changing how contracts behave in other tests.
+#[macro_use]
+extern crate adhesion;
+#[macro_use]
+extern crate galvanic_assert;
+
+use adhesion::{ContractViolation, ViolationKind};
+use std::sync::Mutex;
+
+static VIOLATIONS: Mutex<Vec<(ViolationKind, &'static str)>> = Mutex::new(Vec::new());
+
+fn record(violation: &ContractViolation) {
+    VIOLATIONS
+        .lock()
+        .unwrap()
+        .push((violation.kind, violation.condition_text));
+}
+
+fn record_and_panic(violation: &ContractViolation) {
+    record(violation);
+    panic!("{}", violation);
+}
+
+#[test]
+#[cfg_attr(
+    any(feature = "disable-pre", feature = "disable-post"),
+    ignore = "asserts that checks panic"
+)]
+fn custom_handlers() {
+    contract! {
+        fn clamp_to_ten(x: u32) -> u32 {
+            pre {
+                x <= 10, "{} is out of range", x
+            }
+            body {
+                x.min(10)
+            }
+            post(clamped) {
+                clamped == x
+            }
+        }
+    }
+
+    // Handlers that return let the contracted `fn` carry on.
+    adhesion::set_violation_handler(record);
+    assert!(clamp_to_ten(5) == 5);
+    assert!(clamp_to_ten(15) == 10);
+    assert!(
+        *VIOLATIONS.lock().unwrap()
+            == [
+                (ViolationKind::Pre, "x <= 10"),
+                (ViolationKind::Post, "clamped == x"),
+            ]
+    );
+
+    adhesion::set_violation_handler(record_and_panic);
+    assert_that!(clamp_to_ten(15), panics);
+    assert!(VIOLATIONS.lock().unwrap().len() == 3);
+
+    adhesion::set_violation_handler(adhesion::default_violation_handler);
+    let payload = std::panic::catch_unwind(|| clamp_to_ten(15)).unwrap_err();
+    assert!(payload.downcast_ref::<ContractViolation>().unwrap().kind == ViolationKind::Pre);
+    assert!(VIOLATIONS.lock().unwrap().len() == 3);
+}